@@ -0,0 +1,139 @@
+// Arbitrary-precision integers for the `bigint` feature: an `Integer` that overflows i64
+// becomes a `Value::BigInteger`, and results that fit in an i64 become `Integer` again
+use crate::eval::operators::division_by_zero;
+use crate::value::{FloatNumber, Integer, Value};
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// Keeps one representation per number: `Integer` when it fits in an i64, `BigInteger` otherwise
+pub fn normalize(n: BigInt) -> Value {
+    match n.to_i64() {
+        Some(small) => Value::Integer(Integer(small)),
+        None => Value::BigInteger(n),
+    }
+}
+
+/// The value as a big integer, if it is an integer of either size
+pub fn to_bigint(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::Integer(n) => Some(BigInt::from(n.0)),
+        Value::BigInteger(n) => Some(n.clone()),
+        _ => None,
+    }
+}
+
+/// The nearest float; integers beyond the float range become infinite
+pub fn to_f64(n: &BigInt) -> f64 {
+    n.to_f64().unwrap_or(if n.is_negative() {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    })
+}
+
+/// Parses a decimal integer of any size
+pub fn parse(text: &str) -> Option<Value> {
+    text.parse::<BigInt>().ok().map(normalize)
+}
+
+/// Truncates a float towards zero, e.g. for `int(1e30)`; `None` for NaN and infinities
+pub fn from_f64(f: f64) -> Option<Value> {
+    num_bigint::ToBigInt::to_bigint(&f.trunc()).map(normalize)
+}
+
+/// Applies an operator when either side is a big integer; `None` leaves other operand types to
+/// the caller. Mixed with a float, a big integer is converted to a float, as in Python.
+pub fn apply_operator(left: &Value, right: &Value, op: &str) -> Option<miette::Result<Value>> {
+    if !matches!(left, Value::BigInteger(_)) && !matches!(right, Value::BigInteger(_)) {
+        return None;
+    }
+    if matches!(left, Value::FloatNumber(_)) || matches!(right, Value::FloatNumber(_)) {
+        let as_float = |value: &Value| match value {
+            Value::BigInteger(n) => Value::FloatNumber(FloatNumber(to_f64(n))),
+            other => other.clone(),
+        };
+        return Some(crate::eval::operators::apply_operator(
+            as_float(left),
+            as_float(right),
+            op,
+        ));
+    }
+    let (l, r) = (to_bigint(left)?, to_bigint(right)?);
+    Some(integer_operation(l, r, op))
+}
+
+/// Applies an operator to two integers exactly
+pub fn integer_operation(l: BigInt, r: BigInt, op: &str) -> miette::Result<Value> {
+    if matches!(op, "/" | "//" | "%") && r.is_zero() {
+        return Err(division_by_zero());
+    }
+    let result: BigInt = match op {
+        "+" => l + r,
+        "-" => l - r,
+        "*" => l * r,
+        "//" | "%" => {
+            // Floor like Python: adjust truncating division when the signs differ
+            let (mut quotient, mut remainder) = (&l / &r, &l % &r);
+            if !remainder.is_zero() && remainder.is_negative() != r.is_negative() {
+                quotient -= 1;
+                remainder += &r;
+            }
+            if op == "//" {
+                quotient
+            } else {
+                remainder
+            }
+        }
+        "|" => l | r,
+        "&" => l & r,
+        "^" => l ^ r,
+        "/" => return Ok(Value::FloatNumber(FloatNumber(to_f64(&l) / to_f64(&r)))),
+        "**" if r.is_negative() => {
+            return Ok(Value::FloatNumber(FloatNumber(to_f64(&l).powf(to_f64(&r)))))
+        }
+        "**" => match r.to_u32() {
+            Some(exponent) => l.pow(exponent),
+            // 0, 1 and -1 never grow, so any exponent works
+            None if l.is_zero() || l.magnitude().is_one() => {
+                if r.bit(0) {
+                    l
+                } else {
+                    l.abs()
+                }
+            }
+            None => {
+                return Err(miette::miette!(
+                    code = "OverflowError",
+                    "Error: Exponent {} is too large.",
+                    r
+                ))
+            }
+        },
+        _ => {
+            return Err(miette::miette!(
+                code = "TypeError",
+                "Error: Unsupported operation '{}' between types int and int",
+                op
+            ))
+        }
+    };
+    Ok(normalize(result))
+}
+
+/// Adds up numbers of any size, or `Undefined` if one is not a number
+pub fn sum(values: &[Value]) -> Value {
+    let mut total: Value = Value::Integer(Integer(0));
+    for value in values {
+        if !matches!(
+            value,
+            Value::Integer(_) | Value::BigInteger(_) | Value::FloatNumber(_)
+        ) {
+            return Value::Undefined;
+        }
+        total = match crate::eval::operators::apply_operator(total, value.clone(), "+") {
+            Ok(total) => total,
+            Err(_) => return Value::Undefined,
+        };
+    }
+    total
+}
@@ -0,0 +1,288 @@
+// Equality and ordering between TungLang values: the rules behind ==, !=, <, <=, > and >=
+use crate::value::Value;
+use miette::Result;
+use std::cmp::Ordering;
+
+/// What a value is as far as comparisons go; ints of any size and floats are all numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Number,
+    String,
+    Boolean,
+    Array,
+    Tuple,
+    Dict,
+    Set,
+    Undefined,
+    Function,
+    Struct,
+    StructType,
+    Module,
+}
+
+/// How two values of a pair of kinds are ordered
+#[derive(Debug, Clone, Copy)]
+enum OrderBy {
+    /// By numeric value, exactly, even between ints and floats
+    Number,
+    /// By code point, like Python's strings
+    Text,
+    /// `false` before `true`
+    Boolean,
+    /// Item by item; the first unequal pair decides, then the shorter sequence comes first
+    Items,
+    /// By inclusion: a proper subset is smaller, and unrelated sets are unordered
+    Subset,
+}
+
+/// The pairs of kinds that `<`, `<=`, `>` and `>=` accept. Ordering any other pair is an error,
+/// while `==` and `!=` accept every pair and are simply false or true across kinds.
+const ORDERING_RULES: &[(Kind, Kind, OrderBy)] = &[
+    (Kind::Number, Kind::Number, OrderBy::Number),
+    (Kind::String, Kind::String, OrderBy::Text),
+    (Kind::Boolean, Kind::Boolean, OrderBy::Boolean),
+    (Kind::Array, Kind::Array, OrderBy::Items),
+    (Kind::Tuple, Kind::Tuple, OrderBy::Items),
+    (Kind::Set, Kind::Set, OrderBy::Subset),
+];
+
+fn kind(value: &Value) -> Kind {
+    match value {
+        Value::Integer(_) | Value::FloatNumber(_) => Kind::Number,
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(_) => Kind::Number,
+        Value::String(_) => Kind::String,
+        Value::Boolean(_) => Kind::Boolean,
+        Value::Array(_) => Kind::Array,
+        Value::Tuple(_) => Kind::Tuple,
+        Value::Dict(_) => Kind::Dict,
+        Value::Set(_) => Kind::Set,
+        Value::Undefined => Kind::Undefined,
+        Value::Function { .. } | Value::BuiltinFunction { .. } => Kind::Function,
+        Value::Struct(_) => Kind::Struct,
+        Value::StructType { .. } => Kind::StructType,
+        Value::Module { .. } => Kind::Module,
+    }
+}
+
+/// The `==` operator: deep structural equality that never fails. `1 == 1.0` is true,
+/// `1 == "1"` is false, and NaN is not equal to itself.
+pub fn values_equal(left: &Value, right: &Value) -> bool {
+    equal(left, right, false)
+}
+
+/// Equality for dict keys, set items and `in`: like `==`, except NaN matches NaN so a NaN key
+/// can be found again. This is `Value`'s `PartialEq`.
+pub fn same_value(left: &Value, right: &Value) -> bool {
+    equal(left, right, true)
+}
+
+/// The `is` operator. Values are copied rather than shared, so identity is sameness: the same
+/// type and the same value. Unlike `==`, `1 is 1.0` is false; `x is none` checks for none.
+pub fn identical(left: &Value, right: &Value) -> bool {
+    std::mem::discriminant(left) == std::mem::discriminant(right) && same_value(left, right)
+}
+
+fn equal(left: &Value, right: &Value, nan_is_equal: bool) -> bool {
+    let items_equal = |l: &[Value], r: &[Value]| {
+        l.len() == r.len() && l.iter().zip(r).all(|(a, b)| equal(a, b, nan_is_equal))
+    };
+    match (left, right) {
+        (Value::String(l), Value::String(r)) => l == r,
+        (Value::Boolean(l), Value::Boolean(r)) => l == r,
+        (Value::Array(l), Value::Array(r)) | (Value::Tuple(l), Value::Tuple(r)) => {
+            items_equal(l, r)
+        }
+        // Keys are matched by hash, so only the values need comparing here
+        (Value::Dict(l), Value::Dict(r)) => {
+            l.len() == r.len()
+                && l.iter().all(|(key, value)| {
+                    r.get(key)
+                        .is_some_and(|other| equal(value, other, nan_is_equal))
+                })
+        }
+        (Value::Set(l), Value::Set(r)) => l.len() == r.len() && l.is_subset(r),
+        (Value::Undefined, Value::Undefined) => true,
+        // A function only equals itself: the same definition with the same defaults
+        (
+            Value::Function {
+                parameters: l_parameters,
+                body: l_body,
+                ..
+            },
+            Value::Function {
+                parameters: r_parameters,
+                body: r_body,
+                ..
+            },
+        ) => l_body == r_body && l_parameters == r_parameters,
+        (
+            Value::BuiltinFunction {
+                module: l_module,
+                name: l_name,
+            },
+            Value::BuiltinFunction {
+                module: r_module,
+                name: r_name,
+            },
+        ) => l_module == r_module && l_name == r_name,
+        (Value::Struct(l), Value::Struct(r)) => {
+            l.name == r.name
+                && l.fields.len() == r.fields.len()
+                && l.fields
+                    .iter()
+                    .zip(&r.fields)
+                    .all(|((l_name, a), (r_name, b))| l_name == r_name && equal(a, b, nan_is_equal))
+        }
+        (
+            Value::StructType {
+                name: l_name,
+                fields: l_fields,
+                ..
+            },
+            Value::StructType {
+                name: r_name,
+                fields: r_fields,
+                ..
+            },
+        ) => l_name == r_name && l_fields == r_fields,
+        (
+            Value::Module {
+                name: l_name,
+                members: l_members,
+            },
+            Value::Module {
+                name: r_name,
+                members: r_members,
+            },
+        ) => {
+            l_name == r_name
+                && l_members.len() == r_members.len()
+                && l_members.iter().all(|(name, value)| {
+                    r_members
+                        .get(name)
+                        .is_some_and(|other| equal(value, other, nan_is_equal))
+                })
+        }
+        _ if kind(left) == Kind::Number && kind(right) == Kind::Number => {
+            match compare_numbers(left, right) {
+                Some(ordering) => ordering == Ordering::Equal,
+                None => nan_is_equal && is_nan(left) && is_nan(right),
+            }
+        }
+        _ => false,
+    }
+}
+
+/// How `left` orders against `right`, or `None` when they are unordered, like NaN against
+/// anything or two sets where neither contains the other. Errors for kinds with no ordering.
+pub fn partial_compare(left: &Value, right: &Value, op: &str) -> Result<Option<Ordering>> {
+    let rule: Option<OrderBy> = ORDERING_RULES
+        .iter()
+        .find(|(l, r, _)| *l == kind(left) && *r == kind(right))
+        .map(|(_, _, rule)| *rule);
+    match (rule, left, right) {
+        (Some(OrderBy::Number), _, _) => Ok(compare_numbers(left, right)),
+        (Some(OrderBy::Text), Value::String(l), Value::String(r)) => Ok(Some(l.cmp(r))),
+        (Some(OrderBy::Boolean), Value::Boolean(l), Value::Boolean(r)) => Ok(Some(l.cmp(r))),
+        (
+            Some(OrderBy::Items),
+            Value::Array(l) | Value::Tuple(l),
+            Value::Array(r) | Value::Tuple(r),
+        ) => match l.iter().zip(r).find(|(a, b)| !values_equal(a, b)) {
+            Some((a, b)) => partial_compare(a, b, op),
+            None => Ok(Some(l.len().cmp(&r.len()))),
+        },
+        (Some(OrderBy::Subset), Value::Set(l), Value::Set(r)) => {
+            Ok(match (l.is_subset(r), r.is_subset(l)) {
+                (true, true) => Some(Ordering::Equal),
+                (true, false) => Some(Ordering::Less),
+                (false, true) => Some(Ordering::Greater),
+                (false, false) => None,
+            })
+        }
+        _ => Err(miette::miette!(
+            code = "TypeError",
+            "Error: Cannot compare {} and {} with '{}'.",
+            left.type_name(),
+            right.type_name(),
+            op
+        )),
+    }
+}
+
+/// The `<`, `<=`, `>` and `>=` operators. Unordered values make every one of them false.
+pub fn compare(left: &Value, right: &Value, op: &str) -> Result<bool> {
+    let ordering: Option<Ordering> = partial_compare(left, right, op)?;
+    Ok(ordering.is_some_and(|ordering| match op {
+        "<" => ordering == Ordering::Less,
+        "<=" => ordering != Ordering::Greater,
+        ">" => ordering == Ordering::Greater,
+        _ => ordering != Ordering::Less,
+    }))
+}
+
+/// A total order for `sort`, `min` and `max`. It agrees with `<` wherever `<` is defined, and
+/// fills the gaps so sorting never sees an inconsistent order: NaN sorts after every other
+/// number, sets by size, and values of different kinds by kind.
+pub fn sort_order(left: &Value, right: &Value) -> Ordering {
+    match (left, right) {
+        (Value::Array(l), Value::Array(r)) | (Value::Tuple(l), Value::Tuple(r)) => l
+            .iter()
+            .zip(r)
+            .map(|(a, b)| sort_order(a, b))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| l.len().cmp(&r.len())),
+        (Value::Set(l), Value::Set(r)) => l.len().cmp(&r.len()),
+        _ if kind(left) == Kind::Number && kind(right) == Kind::Number => is_nan(left)
+            .cmp(&is_nan(right))
+            .then_with(|| compare_numbers(left, right).unwrap_or(Ordering::Equal)),
+        _ if kind(left) == kind(right) => partial_compare(left, right, "<")
+            .ok()
+            .flatten()
+            .unwrap_or(Ordering::Equal),
+        _ => kind(left).cmp(&kind(right)),
+    }
+}
+
+fn compare_numbers(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Integer(l), Value::Integer(r)) => Some(l.cmp(r)),
+        (Value::FloatNumber(l), Value::FloatNumber(r)) => l.0.partial_cmp(&r.0),
+        (_, Value::FloatNumber(r)) => compare_integer_float(left, r.0),
+        (Value::FloatNumber(l), _) => compare_integer_float(right, l.0).map(Ordering::reverse),
+        #[cfg(feature = "bigint")]
+        _ => Some(crate::bigint::to_bigint(left)?.cmp(&crate::bigint::to_bigint(right)?)),
+        #[cfg(not(feature = "bigint"))]
+        _ => None,
+    }
+}
+
+/// Compares an integer with a float exactly, so `2 ** 53 + 1 > 2.0 ** 53` even though the
+/// float nearest to the integer is `2.0 ** 53`
+fn compare_integer_float(integer: &Value, f: f64) -> Option<Ordering> {
+    if f.is_nan() {
+        return None;
+    }
+    if f.is_infinite() {
+        return Some(if f > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        });
+    }
+    let whole: f64 = f.trunc();
+    let ordering: Ordering = match integer {
+        // Casting saturates, which still orders correctly beyond the i128 range
+        Value::Integer(n) => i128::from(n.0).cmp(&(whole as i128)),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(n) => n.cmp(&num_bigint::ToBigInt::to_bigint(&whole)?),
+        _ => return None,
+    };
+    // Equal whole parts: the float's fraction decides
+    Some(ordering.then(0.0_f64.partial_cmp(&(f - whole))?))
+}
+
+fn is_nan(value: &Value) -> bool {
+    matches!(value, Value::FloatNumber(f) if f.0.is_nan())
+}
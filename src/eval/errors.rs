@@ -0,0 +1,123 @@
+// Runtime errors as TungLang values: what `catch` binds and `raise` throws.
+// Every runtime error has a kind, carried as the diagnostic's code, such as "TypeError" or
+// "ZeroDivisionError"; an error value is an `Error` struct with `message`, `kind` and `line`.
+use crate::stdlib::StdLib;
+use crate::value::{Integer, MethodTable, StringValue, StructValue, Value};
+use miette::{LabeledSpan, MietteDiagnostic};
+use pest::Span;
+
+/// The struct name of error values, and the kind of errors that have no more specific kind.
+/// `catch Error e` catches errors of every kind.
+pub const ERROR: &str = "Error";
+
+/// Builds an error value
+pub fn error_value(message: String, kind: String, line: Value) -> Value {
+    Value::Struct(StructValue {
+        name: ERROR.to_string(),
+        fields: vec![
+            ("message".to_string(), Value::String(StringValue(message))),
+            ("kind".to_string(), Value::String(StringValue(kind))),
+            ("line".to_string(), line),
+        ],
+        methods: MethodTable::default(),
+    })
+}
+
+/// The kind of a runtime error
+pub fn error_kind(error: &miette::Report) -> String {
+    error
+        .code()
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| ERROR.to_string())
+}
+
+/// The error value `catch` binds for a runtime error. `source` is the program text the error's
+/// labels point into, used to find the line it happened on, unless the error carries the source
+/// of another file it came from.
+pub fn from_report(error: &miette::Report, source: &str) -> Value {
+    let text: String = error.to_string();
+    let message: &str = text.strip_prefix("Error: ").unwrap_or(&text);
+    let line: Value = error
+        .labels()
+        .and_then(|mut labels| labels.next())
+        .and_then(|label| match error.source_code() {
+            Some(own_source) => own_source
+                .read_span(label.inner(), 0, 0)
+                .ok()
+                .map(|contents| contents.line() + 1),
+            None => source
+                .get(..label.offset())
+                .map(|before: &str| before.matches('\n').count() + 1),
+        })
+        .map(|line: usize| Value::Integer(Integer(line as i64)))
+        .unwrap_or(Value::Undefined);
+    error_value(message.to_string(), error_kind(error), line)
+}
+
+/// The runtime error `raise value` throws: a string raises an `Error` with that message, and an
+/// error value raises its own message and kind
+pub fn to_report(value: &Value, span: Span) -> miette::Report {
+    let (message, kind): (String, String) = match value {
+        Value::String(message) => (message.0.clone(), ERROR.to_string()),
+        Value::Struct(error) if error.name == ERROR => {
+            let field = |name: &str| match error.get(name) {
+                Some(Value::String(text)) => text.0.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            (field("message"), field("kind"))
+        }
+        other => {
+            return miette::miette!(
+                code = "TypeError",
+                labels = vec![LabeledSpan::at(span.start()..span.end(), "raised here")],
+                "Error: Can only raise a string or an Error, not {}.",
+                other.type_name()
+            )
+        }
+    };
+    miette::Report::new(
+        MietteDiagnostic::new(format!("Error: {}", message))
+            .with_code(kind)
+            .with_label(LabeledSpan::at(span.start()..span.end(), "raised here")),
+    )
+}
+
+/// Whether a `catch` clause naming `kinds` handles an error of kind `kind`; naming no kinds, or
+/// `Error`, catches everything
+pub fn catches(kinds: &[&str], kind: &str) -> bool {
+    kinds.is_empty() || kinds.iter().any(|k: &&str| *k == kind || *k == ERROR)
+}
+
+/// `Error(message, kind = "Error")`: makes an error value to raise
+pub fn std_error(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    let mut kind: String = ERROR.to_string();
+    for (name, value) in kwargs {
+        match (name.as_str(), value) {
+            ("kind", Value::String(text)) => kind = text.0.clone(),
+            _ => {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    "Error: Error() got an unexpected keyword argument '{}'.",
+                    name
+                ))
+            }
+        }
+    }
+    match args {
+        [Value::String(message)] => Ok(error_value(message.0.clone(), kind, Value::Undefined)),
+        [Value::String(message), Value::String(text)] if kwargs.is_empty() => Ok(error_value(
+            message.0.clone(),
+            text.0.clone(),
+            Value::Undefined,
+        )),
+        _ => Err(miette::miette!(
+            code = "TypeError",
+            "Error: Error() takes a message string and an optional kind string."
+        )),
+    }
+}
@@ -1,110 +1,72 @@
-use crate::interpreter::execute_block;
+use crate::eval::functions::{call_builtin, call_function, evaluate_arguments, CallArguments};
+use crate::eval::methods::call_method;
+use crate::eval::operators::apply_operator;
+use crate::eval::string_literal::{
+    decode_fstring_text, decode_string_literal, escape_error, EscapeError,
+};
+use crate::eval::structs::{assign_path, construct_struct, get_field};
+use crate::format::format_value;
+use crate::interpreter::is_truthy;
 use crate::parser::Rule;
 use crate::stdlib::StdLib;
-use crate::value::{Array, Dict, FloatNumber, Integer, StringValue, Value};
+use crate::value::{
+    Array, BooleanValue, Dict, DictValue, FloatNumber, Integer, SetValue, StringValue, Value,
+};
+use miette::LabeledSpan;
 use pest::iterators::{Pair, Pairs};
-use pest::Parser;
+use pest::Span;
 
 pub fn evaluate_expression(
-    pair: Pair<Rule>,
-    variables: &Dict,
+    pair: Pair<'static, Rule>,
+    variables: &mut Dict,
     stdlib: &StdLib,
 ) -> miette::Result<Value> {
-    use crate::eval::operators::apply_operator;
-
+    let pair: Pair<'static, Rule> = skip_wrappers(pair);
     match pair.as_rule() {
-        Rule::number => {
-            let s: &str = pair.as_str();
-            if s.contains('.') {
-                Ok(Value::FloatNumber(FloatNumber(s.parse::<f64>().unwrap())))
-            } else {
-                Ok(Value::Integer(Integer(s.parse::<i64>().unwrap())))
-            }
-        }
+        Rule::number => evaluate_number(pair),
         Rule::string => {
-            let s: &str = pair.as_str();
-            Ok(Value::String(StringValue(s[1..s.len() - 1].to_string())))
+            let span: Span = pair.as_span();
+            match decode_string_literal(pair.as_str()) {
+                Ok(s) => Ok(Value::String(StringValue(s))),
+                Err(e) => Err(escape_error(e, span.start())),
+            }
         }
+        Rule::fstring => evaluate_fstring(pair, variables, stdlib),
         Rule::IDENTIFIER => {
             let name: &str = pair.as_str();
             match variables.get(name).cloned() {
                 Some(value) => Ok(value),
-                none => Err(miette::miette!(
+                None => Err(miette::miette!(
+                    code = "NameError",
                     "Error: Variable '{}' is not defined.",
                     name
                 )),
             }
         }
-        Rule::function_call => {
+        Rule::function_call => evaluate_call(pair, variables, stdlib),
+        Rule::conditional => {
             let mut inner: Pairs<Rule> = pair.into_inner();
-            let func_name: &str = inner.next().unwrap().as_str();
-            let mut args: Vec<Value> = Vec::new();
-            for p in inner {
-                args.push(evaluate_expression(p, variables, stdlib)?);
-            }
-            if let Some(func) = stdlib.get(func_name) {
-                let result = func(&args);
-                Ok(result)
-            } else if let Some(Value::Function {
-                parameters,
-                body,
-                environment,
-            }) = variables.get(func_name)
-            {
-                let mut local_vars: Dict = environment.clone();
-                for (i, parameter) in parameters.iter().enumerate() {
-                    if let Some(arg) = args.get(i) {
-                        local_vars.insert(parameter.clone(), arg.clone());
-                    }
-                }
-                let parse_result = crate::parser::TungParser::parse(Rule::block, &body);
-                match parse_result {
-                    Ok(mut pairs) => {
-                        let block = pairs.next().unwrap();
-                        match execute_block(block, &mut local_vars, stdlib) {
-                            Ok(_) => Ok(Value::Undefined),
-                            Err(e) => {
-                                let msg = e.to_string();
-                                if msg.starts_with("__RETURN__") {
-                                    let return_str = msg.trim_start_matches("__RETURN__").trim();
-                                    let parse_result = crate::parser::TungParser::parse(
-                                        Rule::expression,
-                                        return_str,
-                                    );
-                                    match parse_result {
-                                        Ok(mut pairs) => {
-                                            let expr = pairs.next().unwrap();
-                                            let val =
-                                                evaluate_expression(expr, &local_vars, stdlib)?;
-                                            Ok(val)
-                                        }
-                                        Err(_) => Ok(Value::Undefined),
-                                    }
-                                } else {
-                                    Err(e)
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => Err(miette::miette!("Function parse error: {}", e)),
-                }
+            let (chosen, condition, other) = (
+                inner.next().unwrap(),
+                inner.next().unwrap(),
+                inner.next().unwrap(),
+            );
+            if is_truthy(evaluate_expression(condition, variables, stdlib)?) {
+                evaluate_expression(chosen, variables, stdlib)
             } else {
-                Err(miette::miette!(
-                    "Error: Function '{}' is not defined.",
-                    func_name
-                ))
+                evaluate_expression(other, variables, stdlib)
             }
         }
-        Rule::comparison | Rule::sum | Rule::term => {
-            let mut inner: Pairs<Rule> = pair.into_inner();
-            let mut left = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
-            while let Some(op_pair) = inner.next() {
-                let op: &str = op_pair.as_str();
-                let right = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
-                left = apply_operator(left, right, op)?;
-            }
-            Ok(left)
+        Rule::comparison => evaluate_comparison(pair, variables, stdlib),
+        Rule::constant => Ok(match pair.as_str() {
+            "true" => Value::Boolean(BooleanValue(true)),
+            "false" => Value::Boolean(BooleanValue(false)),
+            _ => Value::Undefined,
+        }),
+        Rule::bit_or | Rule::bit_xor | Rule::bit_and | Rule::sum | Rule::term | Rule::power => {
+            evaluate_operators(pair, variables, stdlib)
         }
+        Rule::postfix => evaluate_postfix(pair, variables, stdlib),
         Rule::factor => {
             let mut inner: Pairs<Rule> = pair.into_inner();
             let first: Pair<Rule> = inner.next().unwrap();
@@ -117,13 +79,28 @@ pub fn evaluate_expression(
             }
             Ok(Value::Array(elements))
         }
+        Rule::set => {
+            let mut set: SetValue = SetValue::new();
+            for p in pair.into_inner() {
+                set.insert(evaluate_hashable(p, "set item", variables, stdlib)?);
+            }
+            Ok(Value::Set(set))
+        }
+        Rule::tuple | Rule::bare_tuple => {
+            let mut items: Array = Vec::new();
+            for p in pair.into_inner() {
+                items.push(evaluate_expression(p, variables, stdlib)?);
+            }
+            Ok(Value::Tuple(items))
+        }
         Rule::dict => {
-            let mut map: Dict = Dict::new();
+            let mut map: DictValue = DictValue::new();
             for entry in pair.into_inner() {
                 let mut kv: Pairs<Rule> = entry.into_inner();
-                let k: String = kv.next().unwrap().as_str().to_string();
-                let v: Value = evaluate_expression(kv.next().unwrap(), variables, stdlib)?;
-                map.insert(k, v);
+                let key: Value =
+                    evaluate_hashable(kv.next().unwrap(), "dict key", variables, stdlib)?;
+                let value: Value = evaluate_expression(kv.next().unwrap(), variables, stdlib)?;
+                map.insert(key, value);
             }
             Ok(Value::Dict(map))
         }
@@ -133,3 +110,244 @@ pub fn evaluate_expression(
         _ => Err(miette::miette!("Error: Invalid expression.")),
     }
 }
+
+/// An integer or float literal
+fn evaluate_number(pair: Pair<'static, Rule>) -> miette::Result<Value> {
+    let s: &str = pair.as_str();
+    if s.contains('.') {
+        Ok(Value::FloatNumber(FloatNumber(s.parse::<f64>().unwrap())))
+    } else {
+        let span: Span = pair.as_span();
+        #[cfg(feature = "bigint")]
+        if let Some(n) = crate::bigint::parse(s) {
+            return Ok(n);
+        }
+        s.parse::<i64>()
+            .map(|n: i64| Value::Integer(Integer(n)))
+            .map_err(|_| {
+                miette::miette!(
+                    code = "OverflowError",
+                    labels = vec![LabeledSpan::at(span.start()..span.end(), "too large")],
+                    "Error: Integer literal {} does not fit in 64 bits.",
+                    s
+                )
+            })
+    }
+}
+
+/// An f-string: literal text with `{expr}` and `{expr:spec}` fields filled in
+fn evaluate_fstring(
+    pair: Pair<'static, Rule>,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    let mut text: String = String::new();
+    for part in pair.into_inner() {
+        if part.as_rule() == Rule::fstring_text {
+            let span: Span = part.as_span();
+            let decoded: String = decode_fstring_text(part.as_str())
+                .map_err(|e: EscapeError| escape_error(e, span.start()))?;
+            text.push_str(&decoded);
+            continue;
+        }
+        let mut field: Pairs<Rule> = part.into_inner();
+        let value: Value = evaluate_expression(field.next().unwrap(), variables, stdlib)?;
+        match field.next() {
+            Some(spec) => match format_value(&value, spec.as_str()) {
+                Ok(formatted) => text.push_str(&formatted),
+                Err(message) => {
+                    let span: Span = spec.as_span();
+                    return Err(miette::miette!(
+                        code = "ValueError",
+                        labels = vec![LabeledSpan::at(
+                            span.start()..span.end(),
+                            "this format spec"
+                        )],
+                        "Error: Invalid format spec: {}.",
+                        message
+                    ));
+                }
+            },
+            None => text.push_str(&value.to_string()),
+        }
+    }
+    Ok(Value::String(StringValue(text)))
+}
+
+/// A call by name: a user function, a struct constructor or a built-in
+fn evaluate_call(
+    pair: Pair<'static, Rule>,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    let call_span: Span = pair.as_span();
+    let mut inner: Pairs<Rule> = pair.into_inner();
+    let func_name: &str = inner.next().unwrap().as_str();
+    let arguments: CallArguments = evaluate_arguments(inner, variables, stdlib)?;
+    // User definitions shadow built-ins of the same name, as in Python
+    if let Some(function @ Value::Function { .. }) = variables.get(func_name) {
+        call_function(func_name, function, arguments, call_span, stdlib)
+    } else if let Some(Value::StructType {
+        fields, methods, ..
+    }) = variables.get(func_name)
+    {
+        construct_struct(func_name, fields, methods, arguments, call_span)
+    } else if let Some(Value::BuiltinFunction { module, name }) = variables.get(func_name) {
+        // A function taken from a standard library module, as in `from math import round`
+        let module = stdlib
+            .std_module(module)
+            .expect("built-ins come from loaded modules");
+        call_builtin(name, module, arguments, call_span, stdlib)
+    } else {
+        call_builtin(func_name, stdlib.prelude(), arguments, call_span, stdlib)
+    }
+}
+
+/// A comparison. A chain like `0 <= x < 10` means `0 <= x && x < 10`: each operand is
+/// evaluated once, and evaluation stops at the first comparison that is false, as in Python
+fn evaluate_comparison(
+    pair: Pair<'static, Rule>,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    let mut inner: Pairs<Rule> = pair.into_inner();
+    let mut left = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
+    let mut result: Value = Value::Boolean(BooleanValue(true));
+    while let Some(op_pair) = inner.next() {
+        let op: &str = match op_pair.clone().into_inner().next() {
+            Some(identity) if identity.as_rule() == Rule::is_not_op => "is not",
+            _ => op_pair.as_str(),
+        };
+        let right = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
+        result = apply_operator(left, right.clone(), op)
+            .map_err(|e| attach_label(e, op_pair.as_span(), "this operation"))?;
+        if result != Value::Boolean(BooleanValue(true)) {
+            break;
+        }
+        left = right;
+    }
+    Ok(result)
+}
+
+/// Applies the binary operators of one precedence level, like `a + b - c`
+fn evaluate_operators(
+    pair: Pair<'static, Rule>,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    let mut inner: Pairs<Rule> = pair.into_inner();
+    let mut left = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
+    while let Some(op_pair) = inner.next() {
+        let op: &str = op_pair.as_str();
+        let right = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
+        left = apply_operator(left, right, op)
+            .map_err(|e| attach_label(e, op_pair.as_span(), "this operation"))?;
+    }
+    Ok(left)
+}
+
+/// A value followed by field accesses and method calls, like `p.x` or `xs.append(1)`
+fn evaluate_postfix(
+    pair: Pair<'static, Rule>,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    let mut inner: Pairs<Rule> = pair.into_inner();
+    let base: Pair<Rule> = inner.next().unwrap();
+    // The variable (and field path) the current value lives at, so methods can update it
+    let mut place: Option<(String, Vec<String>)> = base
+        .clone()
+        .into_inner()
+        .next()
+        .filter(|p: &Pair<Rule>| p.as_rule() == Rule::IDENTIFIER)
+        .map(|p: Pair<Rule>| (p.as_str().to_string(), Vec::new()));
+    let mut value: Value = evaluate_expression(base, variables, stdlib)?;
+    for suffix in inner {
+        let span: Span = suffix.as_span();
+        match suffix.as_rule() {
+            Rule::field_access => {
+                let field: &str = suffix.into_inner().next().unwrap().as_str();
+                value = get_field(&value, field, span)?;
+                if let Some((_, path)) = place.as_mut() {
+                    path.push(field.to_string());
+                }
+            }
+            _ => {
+                let mut call: Pairs<Rule> = suffix.into_inner();
+                let method_name: &str = call.next().unwrap().as_str();
+                let arguments: CallArguments = evaluate_arguments(call, variables, stdlib)?;
+                let (result, receiver) = call_method(value, method_name, arguments, span, stdlib)?;
+                if let (Some((name, path)), Some(receiver)) = (place.take(), receiver) {
+                    assign_path(variables, &name, &path, receiver)?;
+                }
+                value = result;
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// Steps down through rules that only wrap a single child, like a `sum` without an operator,
+/// so each level of the expression grammar doesn't cost a stack frame in deep recursion
+fn skip_wrappers(mut pair: Pair<'static, Rule>) -> Pair<'static, Rule> {
+    while matches!(
+        pair.as_rule(),
+        Rule::expression
+            | Rule::conditional
+            | Rule::comparison
+            | Rule::bit_or
+            | Rule::bit_xor
+            | Rule::bit_and
+            | Rule::sum
+            | Rule::term
+            | Rule::power
+            | Rule::postfix
+            | Rule::factor
+    ) {
+        let mut inner: Pairs<Rule> = pair.clone().into_inner();
+        match (inner.next(), inner.next()) {
+            (Some(only), None) => pair = only,
+            _ => break,
+        }
+    }
+    pair
+}
+
+/// Evaluates a dict key or set item, which must be hashable
+fn evaluate_hashable(
+    pair: Pair<'static, Rule>,
+    what: &str,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    let span: Span = pair.as_span();
+    let value: Value = evaluate_expression(pair, variables, stdlib)?;
+    if !value.is_hashable() {
+        return Err(miette::miette!(
+            code = "TypeError",
+            labels = vec![LabeledSpan::at(
+                span.start()..span.end(),
+                format!("this {}", what)
+            )],
+            "Error: Unhashable type '{}' cannot be used as a {}.",
+            value.type_name(),
+            what
+        ));
+    }
+    Ok(value)
+}
+
+/// Points an error that has no location yet, e.g. one raised inside a built-in or operator,
+/// at the code that triggered it
+pub fn attach_label(error: miette::Report, span: Span, label: &str) -> miette::Report {
+    if error.labels().is_some() {
+        return error;
+    }
+    let mut diagnostic = miette::MietteDiagnostic::new(error.to_string())
+        .with_label(LabeledSpan::at(span.start()..span.end(), label.to_string()));
+    // Keep the error's kind so `catch` can still tell it apart
+    if let Some(code) = error.code() {
+        diagnostic = diagnostic.with_code(code.to_string());
+    }
+    miette::Report::new(diagnostic)
+}
@@ -0,0 +1,298 @@
+// Handles user-defined function parameters, argument binding and calls for TungLang
+use crate::eval::evaluate_expression::{attach_label, evaluate_expression};
+use crate::interpreter::{execute_block, Flow};
+use crate::parser::Rule;
+use crate::stdlib::{StdLib, StdModule};
+use crate::value::{Dict, Parameter, Value};
+use miette::LabeledSpan;
+use pest::iterators::Pair;
+use pest::Span;
+
+/// Arguments collected at a call site, split into positional and keyword arguments
+pub struct CallArguments {
+    pub positional: Vec<Value>,
+    pub keyword: Vec<(String, Value)>,
+}
+
+/// Evaluates the arguments of a call, keeping keyword arguments separate
+pub fn evaluate_arguments(
+    arguments: impl Iterator<Item = Pair<'static, Rule>>,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<CallArguments> {
+    let mut positional: Vec<Value> = Vec::new();
+    let mut keyword: Vec<(String, Value)> = Vec::new();
+    for argument in arguments {
+        if argument.as_rule() == Rule::keyword_argument {
+            let mut inner = argument.into_inner();
+            let name: String = inner.next().unwrap().as_str().to_string();
+            let value: Value = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
+            keyword.push((name, value));
+        } else if !keyword.is_empty() {
+            let span: Span = argument.as_span();
+            return Err(miette::miette!(
+                code = "SyntaxError",
+                labels = vec![LabeledSpan::at(
+                    span.start()..span.end(),
+                    "positional argument"
+                )],
+                "Error: Positional argument follows keyword argument."
+            ));
+        } else {
+            positional.push(evaluate_expression(argument, variables, stdlib)?);
+        }
+    }
+    Ok(CallArguments {
+        positional,
+        keyword,
+    })
+}
+
+/// Builds the parameter list of a function definition, evaluating defaults at definition time
+pub fn parse_parameters(
+    param_list: Pair<'static, Rule>,
+    variables: &mut Dict,
+    stdlib: &StdLib,
+) -> miette::Result<Vec<Parameter>> {
+    let mut parameters: Vec<Parameter> = Vec::new();
+    for parameter in param_list.into_inner() {
+        let span: Span = parameter.as_span();
+        let mut variadic: bool = false;
+        let mut name: String = String::new();
+        let mut default: Option<Value> = None;
+        for part in parameter.into_inner() {
+            match part.as_rule() {
+                Rule::variadic_marker => variadic = true,
+                Rule::IDENTIFIER => name = part.as_str().to_string(),
+                _ => default = Some(evaluate_expression(part, variables, stdlib)?),
+            }
+        }
+        let label = vec![LabeledSpan::at(span.start()..span.end(), "this parameter")];
+        if parameters.iter().any(|p: &Parameter| p.name == name) {
+            return Err(miette::miette!(
+                code = "SyntaxError",
+                labels = label,
+                "Error: Duplicate parameter '{}'.",
+                name
+            ));
+        }
+        if variadic && default.is_some() {
+            return Err(miette::miette!(
+                code = "SyntaxError",
+                labels = label,
+                "Error: Variadic parameter '{}' cannot have a default value.",
+                name
+            ));
+        }
+        if variadic && parameters.iter().any(|p: &Parameter| p.variadic) {
+            return Err(miette::miette!(
+                code = "SyntaxError",
+                labels = label,
+                "Error: Only one variadic parameter is allowed."
+            ));
+        }
+        let after_variadic: bool = parameters.iter().any(|p: &Parameter| p.variadic);
+        if !variadic
+            && default.is_none()
+            && !after_variadic
+            && parameters.iter().any(|p: &Parameter| p.default.is_some())
+        {
+            return Err(miette::miette!(
+                code = "SyntaxError",
+                labels = label,
+                "Error: Parameter '{}' without a default follows a parameter with a default.",
+                name
+            ));
+        }
+        parameters.push(Parameter {
+            name,
+            default,
+            variadic,
+        });
+    }
+    Ok(parameters)
+}
+
+/// Binds call arguments to parameters, reporting arity errors at the call site
+pub fn bind_arguments(
+    func_name: &str,
+    parameters: &[Parameter],
+    arguments: CallArguments,
+    call_span: Span,
+) -> miette::Result<Vec<(String, Value)>> {
+    let label = || {
+        vec![LabeledSpan::at(
+            call_span.start()..call_span.end(),
+            "in this call",
+        )]
+    };
+    let positional_count: usize = parameters
+        .iter()
+        .take_while(|p: &&Parameter| !p.variadic)
+        .count();
+    let has_variadic: bool = positional_count < parameters.len();
+
+    let mut bound: Vec<Option<Value>> = vec![None; parameters.len()];
+    let mut positional = arguments.positional.into_iter();
+    for slot in bound.iter_mut().take(positional_count) {
+        match positional.next() {
+            Some(value) => *slot = Some(value),
+            None => break,
+        }
+    }
+    let rest: Vec<Value> = positional.collect();
+    if has_variadic {
+        bound[positional_count] = Some(Value::Array(rest));
+    } else if !rest.is_empty() {
+        return Err(miette::miette!(
+            code = "TypeError",
+            labels = label(),
+            "Error: Function '{}' takes {} positional argument{} but {} were given.",
+            func_name,
+            positional_count,
+            if positional_count == 1 { "" } else { "s" },
+            positional_count + rest.len()
+        ));
+    }
+
+    for (name, value) in arguments.keyword {
+        let index = parameters
+            .iter()
+            .position(|p: &Parameter| p.name == name && !p.variadic);
+        match index {
+            Some(i) if bound[i].is_some() => {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    labels = label(),
+                    "Error: Function '{}' got multiple values for argument '{}'.",
+                    func_name,
+                    name
+                ));
+            }
+            Some(i) => bound[i] = Some(value),
+            None => {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    labels = label(),
+                    "Error: Function '{}' got an unexpected keyword argument '{}'.",
+                    func_name,
+                    name
+                ));
+            }
+        }
+    }
+
+    let mut bindings: Vec<(String, Value)> = Vec::with_capacity(parameters.len());
+    for (parameter, value) in parameters.iter().zip(bound) {
+        match value.or_else(|| parameter.default.clone()) {
+            Some(value) => bindings.push((parameter.name.clone(), value)),
+            None => {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    labels = label(),
+                    "Error: Function '{}' is missing required argument '{}'.",
+                    func_name,
+                    parameter.name
+                ));
+            }
+        }
+    }
+    Ok(bindings)
+}
+
+/// Calls a user-defined function with already evaluated arguments
+pub fn call_function(
+    func_name: &str,
+    function: &Value,
+    arguments: CallArguments,
+    call_span: Span,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    // The environment is captured before the function exists, so re-bind it for recursion under
+    // the name it was defined with: the body calls itself by that name, whatever it is called here
+    let recursion: Option<(String, Value)> = match function {
+        Value::Function { name, .. } => Some((name.clone(), function.clone())),
+        _ => None,
+    };
+    invoke_function(func_name, function, recursion, arguments, call_span, stdlib)
+        .map(|(value, _)| value)
+}
+
+/// Calls a built-in function of the prelude or of a standard library module
+pub fn call_builtin(
+    func_name: &str,
+    module: &StdModule,
+    arguments: CallArguments,
+    call_span: Span,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    let label = |text: &str| {
+        vec![LabeledSpan::at(
+            call_span.start()..call_span.end(),
+            text.to_string(),
+        )]
+    };
+    if let Some(func) = module.get_stateful(func_name) {
+        func(stdlib, &arguments.positional, &arguments.keyword)
+            .map_err(|e| attach_label(e, call_span, "in this call"))
+    } else if let Some(func) = module.get(func_name) {
+        if !arguments.keyword.is_empty() {
+            return Err(miette::miette!(
+                code = "TypeError",
+                labels = label("in this call"),
+                "Error: Built-in function '{}' does not accept keyword arguments.",
+                func_name
+            ));
+        }
+        Ok(func(&arguments.positional))
+    } else {
+        Err(miette::miette!(
+            code = "NameError",
+            labels = label("called here"),
+            "Error: Function '{}' is not defined.",
+            func_name
+        ))
+    }
+}
+
+/// Runs a function body, handing back both the returned value and the final local variables
+pub fn invoke_function(
+    func_name: &str,
+    function: &Value,
+    extra_binding: Option<(String, Value)>,
+    arguments: CallArguments,
+    call_span: Span,
+    stdlib: &StdLib,
+) -> miette::Result<(Value, Dict)> {
+    let Value::Function {
+        parameters,
+        body,
+        environment,
+        ..
+    } = function
+    else {
+        return Err(miette::miette!(
+            code = "TypeError",
+            "Error: '{}' is not a function.",
+            func_name
+        ));
+    };
+    let mut local_vars: Dict = environment.clone();
+    if let Some((name, value)) = extra_binding {
+        local_vars.insert(name, value);
+    }
+    for (name, value) in bind_arguments(func_name, parameters, arguments, call_span)? {
+        local_vars.insert(name, value);
+    }
+    // A function imported from another file reports its errors with that file's source
+    let flow: Flow = execute_block(body.clone(), &mut local_vars, stdlib).map_err(|e| {
+        stdlib
+            .modules()
+            .attach_source(e, body.as_span().get_input(), call_span.get_input())
+    })?;
+    let value: Value = match flow {
+        Flow::Return(value) => value,
+        Flow::Next => Value::Undefined,
+    };
+    Ok((value, local_vars))
+}
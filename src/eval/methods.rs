@@ -0,0 +1,545 @@
+// Dispatches `.method(args)` calls to struct methods, module functions or the built-in method
+// tables, which are dispatched on the receiver's type. The prelude's receiver-first functions,
+// such as `split(s, ",")`, run the same built-in methods.
+use crate::eval::evaluate_expression::attach_label;
+use crate::eval::functions::{call_builtin, call_function, CallArguments};
+use crate::eval::std::std_dict;
+use crate::eval::std::std_len::std_len;
+use crate::eval::std::std_list;
+use crate::eval::std::std_math::unexpected_keyword;
+use crate::eval::std::std_set;
+use crate::eval::std::std_string;
+use crate::eval::structs::{call_struct_method, construct_struct};
+use crate::stdlib::StdLib;
+use crate::value::{Integer, Value};
+use miette::LabeledSpan;
+use pest::Span;
+use std::collections::HashMap;
+
+/// Calls a method on a receiver, returning the result and the receiver as the method left it
+pub fn call_method(
+    receiver: Value,
+    method_name: &str,
+    arguments: CallArguments,
+    call_span: Span,
+    stdlib: &StdLib,
+) -> miette::Result<(Value, Option<Value>)> {
+    if let Value::Struct(_) | Value::StructType { .. } = receiver {
+        return call_struct_method(receiver, method_name, arguments, call_span, stdlib);
+    }
+    let label = || {
+        vec![LabeledSpan::at(
+            call_span.start()..call_span.end(),
+            "in this call",
+        )]
+    };
+    if let Value::Module { name, members } = &receiver {
+        let result: Value = match members.get(method_name) {
+            Some(function @ Value::Function { .. }) => {
+                call_function(method_name, function, arguments, call_span, stdlib)?
+            }
+            Some(Value::StructType {
+                fields, methods, ..
+            }) => construct_struct(method_name, fields, methods, arguments, call_span)?,
+            Some(Value::BuiltinFunction { module, name }) => {
+                let module = stdlib
+                    .std_module(module)
+                    .expect("built-ins come from loaded modules");
+                call_builtin(name, module, arguments, call_span, stdlib)?
+            }
+            Some(other) => {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    labels = label(),
+                    "Error: '{}.{}' is a {}, not a function.",
+                    name,
+                    method_name,
+                    other.type_name()
+                ))
+            }
+            None => {
+                return Err(miette::miette!(
+                    code = "AttributeError",
+                    labels = label(),
+                    "Error: Module '{}' has no function '{}'.",
+                    name,
+                    method_name
+                ))
+            }
+        };
+        // A module's names cannot be changed from outside, so there is no receiver to update
+        return Ok((result, None));
+    }
+    let Some(method) = stdlib.get_method(&receiver, method_name) else {
+        return Err(miette::miette!(
+            code = "AttributeError",
+            labels = label(),
+            "Error: Type '{}' has no method '{}'.",
+            receiver.type_name(),
+            method_name
+        ));
+    };
+    if !arguments.keyword.is_empty() {
+        return Err(miette::miette!(
+            code = "TypeError",
+            labels = label(),
+            "Error: Built-in method '{}' does not accept keyword arguments.",
+            method_name
+        ));
+    }
+    let mut receiver: Value = receiver;
+    let result: Value = method
+        .call(method_name, &mut receiver, &arguments.positional, 0)
+        .map_err(|e| attach_label(e, call_span, "in this call"))?;
+    Ok((result, Some(receiver)))
+}
+
+/// What a built-in method accepts as one of its arguments
+#[derive(Clone, Copy)]
+enum Kind {
+    Any,
+    /// A value that can be a dict key or set item
+    Hashable,
+    String,
+    /// A non-empty string to split on
+    Separator,
+    Integer,
+    Array,
+    Dict,
+}
+
+impl Kind {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            Kind::Any => true,
+            Kind::Hashable => value.is_hashable(),
+            Kind::String | Kind::Separator => matches!(value, Value::String(_)),
+            Kind::Integer => matches!(value, Value::Integer(_)),
+            Kind::Array => matches!(value, Value::Array(_)),
+            Kind::Dict => matches!(value, Value::Dict(_)),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Kind::Any => "a value",
+            Kind::Hashable => "a hashable value",
+            Kind::String | Kind::Separator => "a string",
+            Kind::Integer => "an int",
+            Kind::Array => "an array",
+            Kind::Dict => "a dict",
+        }
+    }
+}
+
+/// A built-in method: the arguments it takes after its receiver, and what it does with them
+pub struct BuiltinMethod {
+    required: &'static [Kind],
+    optional: &'static [Kind],
+    /// Whether any number of further arguments may follow, as in `"{} {}".format(a, b)`
+    variadic: bool,
+    run: fn(receiver: &mut Value, args: &[Value]) -> miette::Result<Value>,
+}
+
+impl BuiltinMethod {
+    /// Checks the arguments, then runs the method. `receivers` is 1 when the receiver was passed
+    /// as an argument too, as in `split(s, ",")`, so the counts in errors match the call.
+    fn call(
+        &self,
+        name: &str,
+        receiver: &mut Value,
+        args: &[Value],
+        receivers: usize,
+    ) -> miette::Result<Value> {
+        let (min, max): (usize, usize) = (
+            self.required.len(),
+            self.required.len() + self.optional.len(),
+        );
+        if args.len() < min || (args.len() > max && !self.variadic) {
+            let expected: String = if min == max {
+                (min + receivers).to_string()
+            } else {
+                format!("{} to {}", min + receivers, max + receivers)
+            };
+            let given: usize = args.len() + receivers;
+            return Err(miette::miette!(
+                code = "TypeError",
+                "Error: {}() takes {} argument{} but {} {} given.",
+                name,
+                expected,
+                if max + receivers == 1 { "" } else { "s" },
+                given,
+                if given == 1 { "was" } else { "were" }
+            ));
+        }
+        for (kind, arg) in self.required.iter().chain(self.optional).zip(args) {
+            if !kind.accepts(arg) {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    "Error: {}() needs {}, not {}.",
+                    name,
+                    kind.describe(),
+                    arg.type_name()
+                ));
+            }
+            if let (Kind::Separator, Value::String(separator)) = (kind, arg) {
+                if separator.is_empty() {
+                    return Err(miette::miette!(
+                        code = "ValueError",
+                        "Error: {}() cannot split on an empty separator.",
+                        name
+                    ));
+                }
+            }
+        }
+        (self.run)(receiver, args)
+    }
+}
+
+const fn method(
+    required: &'static [Kind],
+    optional: &'static [Kind],
+    run: fn(&mut Value, &[Value]) -> miette::Result<Value>,
+) -> BuiltinMethod {
+    BuiltinMethod {
+        required,
+        optional,
+        variadic: false,
+        run,
+    }
+}
+
+/// Wraps a free function so the receiver is passed as its first argument
+macro_rules! receiver_first {
+    ($function:path) => {
+        |receiver: &mut Value, args: &[Value]| Ok($function(&with_receiver(receiver, args)))
+    };
+}
+
+/// The prelude functions named after methods that change their receiver; called as functions,
+/// they return the changed copy, as in `xs = append(xs, 1)`
+const RETURN_RECEIVER: &[&str] = &["append", "insert", "sort", "update", "add", "remove"];
+
+pub struct Methods {
+    string: HashMap<&'static str, BuiltinMethod>,
+    array: HashMap<&'static str, BuiltinMethod>,
+    dict: HashMap<&'static str, BuiltinMethod>,
+    set: HashMap<&'static str, BuiltinMethod>,
+}
+
+impl Methods {
+    pub fn new() -> Self {
+        let mut string: HashMap<&'static str, BuiltinMethod> = HashMap::new();
+        let mut array: HashMap<&'static str, BuiltinMethod> = HashMap::new();
+        let mut dict: HashMap<&'static str, BuiltinMethod> = HashMap::new();
+        let mut set: HashMap<&'static str, BuiltinMethod> = HashMap::new();
+
+        // String methods
+        let text: &[Kind] = &[Kind::String];
+        string.insert("len", method(&[], &[], length));
+        string.insert(
+            "index",
+            method(text, &[], receiver_first!(std_list::std_index)),
+        );
+        string.insert(
+            "split",
+            method(
+                &[],
+                &[Kind::Separator, Kind::Integer],
+                receiver_first!(std_string::std_split),
+            ),
+        );
+        string.insert(
+            "join",
+            method(&[Kind::Array], &[], receiver_first!(std_string::std_join)),
+        );
+        string.insert(
+            "strip",
+            method(&[], text, receiver_first!(std_string::std_strip)),
+        );
+        string.insert(
+            "lstrip",
+            method(&[], text, receiver_first!(std_string::std_lstrip)),
+        );
+        string.insert(
+            "rstrip",
+            method(&[], text, receiver_first!(std_string::std_rstrip)),
+        );
+        string.insert(
+            "replace",
+            method(
+                &[Kind::String, Kind::String],
+                &[Kind::Integer],
+                receiver_first!(std_string::std_replace),
+            ),
+        );
+        string.insert(
+            "upper",
+            method(&[], &[], receiver_first!(std_string::std_upper)),
+        );
+        string.insert(
+            "lower",
+            method(&[], &[], receiver_first!(std_string::std_lower)),
+        );
+        string.insert(
+            "startswith",
+            method(text, &[], receiver_first!(std_string::std_startswith)),
+        );
+        string.insert(
+            "endswith",
+            method(text, &[], receiver_first!(std_string::std_endswith)),
+        );
+        string.insert(
+            "find",
+            method(text, &[], receiver_first!(std_string::std_find)),
+        );
+        string.insert(
+            "count",
+            method(text, &[], receiver_first!(std_string::std_count)),
+        );
+        string.insert(
+            "isdigit",
+            method(&[], &[], receiver_first!(std_string::std_isdigit)),
+        );
+        string.insert(
+            "isalpha",
+            method(&[], &[], receiver_first!(std_string::std_isalpha)),
+        );
+        string.insert(
+            "chars",
+            method(&[], &[], receiver_first!(std_string::std_chars)),
+        );
+        string.insert(
+            "format",
+            BuiltinMethod {
+                variadic: true,
+                ..method(&[], &[], |receiver: &mut Value, args: &[Value]| {
+                    std_string::std_format(&with_receiver(receiver, args))
+                })
+            },
+        );
+
+        // Array methods (like Python's list methods, these update the array in place)
+        array.insert("len", method(&[], &[], length));
+        array.insert(
+            "append",
+            method(&[Kind::Any], &[], |receiver: &mut Value, args: &[Value]| {
+                if let Value::Array(array) = receiver {
+                    array.push(args[0].clone());
+                }
+                Ok(Value::Undefined)
+            }),
+        );
+        array.insert(
+            "insert",
+            method(
+                &[Kind::Integer, Kind::Any],
+                &[],
+                |receiver: &mut Value, args: &[Value]| {
+                    *receiver = std_list::std_insert(&with_receiver(receiver, args));
+                    Ok(Value::Undefined)
+                },
+            ),
+        );
+        array.insert("pop", method(&[], &[Kind::Integer], pop));
+        array.insert(
+            "index",
+            method(&[Kind::Any], &[], receiver_first!(std_list::std_index)),
+        );
+        array.insert(
+            "sort",
+            method(&[], &[], |receiver: &mut Value, _| {
+                *receiver = std_list::std_sort(std::slice::from_ref(receiver));
+                Ok(Value::Undefined)
+            }),
+        );
+
+        // Dict methods
+        dict.insert("len", method(&[], &[], length));
+        dict.insert(
+            "keys",
+            method(&[], &[], receiver_first!(std_dict::std_keys)),
+        );
+        dict.insert(
+            "values",
+            method(&[], &[], receiver_first!(std_dict::std_values)),
+        );
+        dict.insert(
+            "items",
+            method(&[], &[], receiver_first!(std_dict::std_items)),
+        );
+        dict.insert(
+            "get",
+            method(
+                &[Kind::Hashable],
+                &[Kind::Any],
+                receiver_first!(std_dict::std_get),
+            ),
+        );
+        // remove and update change the dict in place, like Python's dict.pop and dict.update
+        dict.insert(
+            "remove",
+            method(
+                &[Kind::Hashable],
+                &[],
+                |receiver: &mut Value, args: &[Value]| {
+                    Ok(match receiver {
+                        Value::Dict(map) => map.remove(&args[0]).unwrap_or(Value::Undefined),
+                        _ => Value::Undefined,
+                    })
+                },
+            ),
+        );
+        dict.insert(
+            "update",
+            method(
+                &[Kind::Dict],
+                &[],
+                |receiver: &mut Value, args: &[Value]| {
+                    if let (Value::Dict(map), Value::Dict(other)) = (receiver, &args[0]) {
+                        map.update(other);
+                    }
+                    Ok(Value::Undefined)
+                },
+            ),
+        );
+
+        // Set methods (add and remove change the set in place)
+        set.insert("len", method(&[], &[], length));
+        set.insert(
+            "add",
+            method(
+                &[Kind::Hashable],
+                &[],
+                |receiver: &mut Value, args: &[Value]| {
+                    *receiver = std_set::std_add(&with_receiver(receiver, args));
+                    Ok(Value::Undefined)
+                },
+            ),
+        );
+        set.insert(
+            "remove",
+            method(
+                &[Kind::Hashable],
+                &[],
+                |receiver: &mut Value, args: &[Value]| {
+                    *receiver = std_set::std_remove(&with_receiver(receiver, args));
+                    Ok(Value::Undefined)
+                },
+            ),
+        );
+
+        Self {
+            string,
+            array,
+            dict,
+            set,
+        }
+    }
+
+    /// Looks up a method for the receiver's type
+    pub fn get(&self, receiver: &Value, name: &str) -> Option<&BuiltinMethod> {
+        match receiver {
+            Value::String(_) => self.string.get(name),
+            Value::Array(_) => self.array.get(name),
+            Value::Dict(_) => self.dict.get(name),
+            Value::Set(_) => self.set.get(name),
+            _ => None,
+        }
+    }
+
+    /// Runs a prelude function that takes its receiver first, such as `split(s, ",")`, as the
+    /// receiver's method of the same name
+    pub fn call_function(
+        &self,
+        name: &str,
+        args: &[Value],
+        kwargs: &[(String, Value)],
+    ) -> miette::Result<Value> {
+        if let Some((keyword, _)) = kwargs.first() {
+            return Err(unexpected_keyword(name, keyword));
+        }
+        let Some((receiver, rest)) = args.split_first() else {
+            return Err(miette::miette!(
+                code = "TypeError",
+                "Error: {}() takes at least 1 argument but 0 were given.",
+                name
+            ));
+        };
+        let Some(method) = self.get(receiver, name) else {
+            let types: Vec<&str> = [
+                ("a string", &self.string),
+                ("an array", &self.array),
+                ("a dict", &self.dict),
+                ("a set", &self.set),
+            ]
+            .into_iter()
+            .filter(|(_, table)| table.contains_key(name))
+            .map(|(type_name, _)| type_name)
+            .collect();
+            return Err(miette::miette!(
+                code = "TypeError",
+                "Error: {}() needs {}, not {}.",
+                name,
+                types.join(" or "),
+                receiver.type_name()
+            ));
+        };
+        let mut receiver: Value = receiver.clone();
+        let result: Value = method.call(name, &mut receiver, rest, 1)?;
+        Ok(if RETURN_RECEIVER.contains(&name) {
+            receiver
+        } else {
+            result
+        })
+    }
+}
+
+impl Default for Methods {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn length(receiver: &mut Value, _args: &[Value]) -> miette::Result<Value> {
+    Ok(std_len(receiver))
+}
+
+/// Removes and returns the item at an index (from the end when negative), the last by default
+fn pop(receiver: &mut Value, args: &[Value]) -> miette::Result<Value> {
+    let Value::Array(array) = receiver else {
+        return Ok(Value::Undefined);
+    };
+    if array.is_empty() {
+        return Err(miette::miette!(
+            code = "IndexError",
+            "Error: Cannot pop from an empty array."
+        ));
+    }
+    let index: i64 = match args.first() {
+        Some(Value::Integer(Integer(index))) => *index,
+        _ => -1,
+    };
+    let position: Option<usize> = if index < 0 {
+        array.len().checked_sub(index.unsigned_abs() as usize)
+    } else {
+        Some(index as usize)
+    };
+    match position {
+        Some(position) if position < array.len() => Ok(array.remove(position)),
+        _ => Err(miette::miette!(
+            code = "IndexError",
+            "Error: Pop index {} is out of range for an array of {}.",
+            index,
+            array.len()
+        )),
+    }
+}
+
+/// Builds a free-function argument list with the receiver first, e.g. `index(s, "a")`
+fn with_receiver(receiver: &Value, args: &[Value]) -> Vec<Value> {
+    let mut all: Vec<Value> = Vec::with_capacity(args.len() + 1);
+    all.push(receiver.clone());
+    all.extend_from_slice(args);
+    all
+}
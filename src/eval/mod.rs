@@ -1,4 +1,10 @@
 // TungLang evaluation logic module
+pub mod compare;
+pub mod errors;
 pub mod evaluate_expression;
+pub mod functions;
+pub mod methods;
 pub mod operators;
 pub mod std;
+pub mod string_literal;
+pub mod structs;
@@ -1,23 +1,73 @@
 // Handles arithmetic and logical operators for TungLang
-use crate::value::Value;
+use crate::eval::compare::{compare, identical, values_equal};
+use crate::format::to_str;
+use crate::value::{Integer, Value};
 use miette::Result;
 
 /// Applies a binary operator to two Values
 pub fn apply_operator(left: Value, right: Value, op: &str) -> Result<Value> {
-    use crate::value::{BooleanValue, FloatNumber, Integer, StringValue};
+    use crate::value::{BooleanValue, FloatNumber, StringValue};
+    // Comparisons follow one set of rules for every type; see `compare`
+    match op {
+        "==" => return Ok(Value::Boolean(BooleanValue(values_equal(&left, &right)))),
+        "!=" => return Ok(Value::Boolean(BooleanValue(!values_equal(&left, &right)))),
+        "is" => return Ok(Value::Boolean(BooleanValue(identical(&left, &right)))),
+        "is not" => return Ok(Value::Boolean(BooleanValue(!identical(&left, &right)))),
+        "<" | "<=" | ">" | ">=" => {
+            return compare(&left, &right, op)
+                .map(|result: bool| Value::Boolean(BooleanValue(result)))
+        }
+        _ => {}
+    }
+    #[cfg(feature = "bigint")]
+    if let Some(result) = crate::bigint::apply_operator(&left, &right, op) {
+        return result;
+    }
     match (left.clone(), right.clone(), op) {
+        // Dividing any number by zero is an error, as in Python
+        (
+            Value::Integer(_) | Value::FloatNumber(_),
+            Value::Integer(Integer(0)),
+            "/" | "//" | "%",
+        ) => Err(division_by_zero()),
+        (Value::Integer(_) | Value::FloatNumber(_), Value::FloatNumber(r), "/" | "//" | "%")
+            if r.0 == 0.0 =>
+        {
+            Err(division_by_zero())
+        }
+
         // Arithmetic - Python-like behavior with auto-promotion to float
-        (Value::Integer(l), Value::Integer(r), "+") => Ok(Value::Integer(l + r)),
-        (Value::Integer(l), Value::Integer(r), "-") => Ok(Value::Integer(l - r)),
-        (Value::Integer(l), Value::Integer(r), "*") => Ok(Value::Integer(l * r)),
+        (Value::Integer(l), Value::Integer(r), "+" | "-" | "*" | "//" | "%") => {
+            checked_integer_operation(l, r, op)
+        }
         (Value::Integer(l), Value::Integer(r), "/") => {
             Ok(Value::FloatNumber(FloatNumber(l.0 as f64 / r.0 as f64)))
         } // Division always returns float in Python
-        (Value::Integer(l), Value::Integer(r), "//") => Ok(Value::Integer(Integer(l.0 / r.0))), // Floor division
-        (Value::Integer(l), Value::Integer(r), "%") => Ok(Value::Integer(Integer(l.0 % r.0))),
+        // Zero to a negative power divides by zero, as in Python
+        (Value::Integer(Integer(0)), Value::Integer(r), "**") if r.0 < 0 => Err(division_by_zero()),
+        (Value::FloatNumber(l), Value::Integer(Integer(r)), "**") if l.0 == 0.0 && r < 0 => {
+            Err(division_by_zero())
+        }
+        (Value::Integer(Integer(0)), Value::FloatNumber(r), "**") if r.0 < 0.0 => {
+            Err(division_by_zero())
+        }
+        (Value::FloatNumber(l), Value::FloatNumber(r), "**") if l.0 == 0.0 && r.0 < 0.0 => {
+            Err(division_by_zero())
+        }
+        // Exponentiation: exact for a non-negative exponent, a float otherwise, as in Python
+        (Value::Integer(l), Value::Integer(r), "**") if r.0 >= 0 => checked_integer_power(l, r),
         (Value::Integer(l), Value::Integer(r), "**") => Ok(Value::FloatNumber(FloatNumber(
             (l.0 as f64).powf(r.0 as f64),
-        ))), // Exponentiation
+        ))),
+
+        // Floor division and modulo with a float operand give a float, rounded towards
+        // negative infinity, with the remainder taking the divisor's sign, as in Python
+        (Value::FloatNumber(_), Value::Integer(_) | Value::FloatNumber(_), "//" | "%")
+        | (Value::Integer(_), Value::FloatNumber(_), "//" | "%") => {
+            let (quotient, remainder) = float_floor_divmod(as_f64(&left), as_f64(&right));
+            let result: f64 = if op == "//" { quotient } else { remainder };
+            Ok(Value::FloatNumber(FloatNumber(result)))
+        }
 
         // Mixed number and float operations (auto-promotion)
         (Value::Integer(l), Value::FloatNumber(r), "+") => {
@@ -32,12 +82,6 @@ pub fn apply_operator(left: Value, right: Value, op: &str) -> Result<Value> {
         (Value::Integer(l), Value::FloatNumber(r), "/") => {
             Ok(Value::FloatNumber(FloatNumber(l.0 as f64 / r.0)))
         }
-        (Value::Integer(l), Value::FloatNumber(r), "//") => {
-            Ok(Value::Integer(Integer((l.0 as f64 / r.0).floor() as i64)))
-        }
-        (Value::Integer(l), Value::FloatNumber(r), "%") => {
-            Ok(Value::FloatNumber(FloatNumber((l.0 as f64) % r.0)))
-        }
         (Value::Integer(l), Value::FloatNumber(r), "**") => {
             Ok(Value::FloatNumber(FloatNumber((l.0 as f64).powf(r.0))))
         }
@@ -54,12 +98,6 @@ pub fn apply_operator(left: Value, right: Value, op: &str) -> Result<Value> {
         (Value::FloatNumber(l), Value::Integer(r), "/") => {
             Ok(Value::FloatNumber(FloatNumber(l.0 / r.0 as f64)))
         }
-        (Value::FloatNumber(l), Value::Integer(r), "//") => {
-            Ok(Value::Integer(Integer((l.0 / r.0 as f64).floor() as i64)))
-        }
-        (Value::FloatNumber(l), Value::Integer(r), "%") => {
-            Ok(Value::FloatNumber(FloatNumber(l.0 % r.0 as f64)))
-        }
         (Value::FloatNumber(l), Value::Integer(r), "**") => {
             Ok(Value::FloatNumber(FloatNumber(l.0.powf(r.0 as f64))))
         }
@@ -76,54 +114,13 @@ pub fn apply_operator(left: Value, right: Value, op: &str) -> Result<Value> {
         (Value::FloatNumber(l), Value::FloatNumber(r), "/") => {
             Ok(Value::FloatNumber(FloatNumber(l.0 / r.0)))
         }
-        (Value::FloatNumber(l), Value::FloatNumber(r), "//") => {
-            Ok(Value::Integer(Integer((l.0 / r.0).floor() as i64)))
-        }
-        (Value::FloatNumber(l), Value::FloatNumber(r), "%") => {
-            Ok(Value::FloatNumber(FloatNumber(l.0 % r.0)))
-        }
         (Value::FloatNumber(l), Value::FloatNumber(r), "**") => {
             Ok(Value::FloatNumber(FloatNumber(l.0.powf(r.0))))
         }
         // String concatenation and Python-like string operations
         (Value::String(l), Value::String(r), "+") => Ok(Value::String(l + &r)),
-        (Value::String(l), Value::Integer(r), "+") => {
-            Ok(Value::String(l + &StringValue(r.0.to_string())))
-        }
-        (Value::String(l), Value::FloatNumber(r), "+") => {
-            Ok(Value::String(l + &StringValue(r.0.to_string())))
-        }
-        (Value::String(l), Value::Boolean(r), "+") => {
-            Ok(Value::String(l + &StringValue(r.0.to_string())))
-        }
-        (Value::String(l), Value::Array(r), "+") => {
-            Ok(Value::String(l + &StringValue(format!("{:?}", r))))
-        }
-        (Value::String(l), Value::Dict(r), "+") => {
-            Ok(Value::String(l + &StringValue(format!("{:?}", r))))
-        }
-        (Value::String(l), Value::Undefined, "+") => {
-            Ok(Value::String(l + &StringValue("undefined".to_string())))
-        }
-
-        (Value::Integer(l), Value::String(r), "+") => {
-            Ok(Value::String(StringValue(l.0.to_string()) + &r))
-        }
-        (Value::FloatNumber(l), Value::String(r), "+") => {
-            Ok(Value::String(StringValue(l.0.to_string()) + &r))
-        }
-        (Value::Boolean(l), Value::String(r), "+") => {
-            Ok(Value::String(StringValue(l.0.to_string()) + &r))
-        }
-        (Value::Array(l), Value::String(r), "+") => {
-            Ok(Value::String(StringValue(format!("{:?}", l)) + &r))
-        }
-        (Value::Dict(l), Value::String(r), "+") => {
-            Ok(Value::String(StringValue(format!("{:?}", l)) + &r))
-        }
-        (Value::Undefined, Value::String(r), "+") => {
-            Ok(Value::String(StringValue("undefined".to_string()) + &r))
-        }
+        (Value::String(l), right, "+") => Ok(Value::String(l + &StringValue(to_str(&right)))),
+        (left, Value::String(r), "+") => Ok(Value::String(StringValue(to_str(&left)) + &r)),
 
         // Python-like string repetition with * operator
         (Value::String(s), Value::Integer(n), "*") => {
@@ -140,6 +137,13 @@ pub fn apply_operator(left: Value, right: Value, op: &str) -> Result<Value> {
                 Ok(Value::String(s.repeat(n.0 as usize)))
             }
         }
+
+        // Tuple concatenation: (1, 2) + (3,)
+        (Value::Tuple(mut l), Value::Tuple(r), "+") => {
+            l.extend(r);
+            Ok(Value::Tuple(l))
+        }
+
         // Array concatenation and other Python-like array operations
         (Value::Array(mut l), Value::Array(r), "+") => {
             l.extend(r);
@@ -177,53 +181,6 @@ pub fn apply_operator(left: Value, right: Value, op: &str) -> Result<Value> {
                 Ok(Value::Array(result))
             }
         }
-        // Equality
-        (Value::Integer(l), Value::Integer(r), "==") => Ok(Value::Boolean(BooleanValue(l == r))),
-        (Value::FloatNumber(l), Value::FloatNumber(r), "==") => {
-            Ok(Value::Boolean(BooleanValue(l == r)))
-        }
-        (Value::String(l), Value::String(r), "==") => Ok(Value::Boolean(BooleanValue(l == r))),
-        (Value::Boolean(l), Value::Boolean(r), "==") => Ok(Value::Boolean(BooleanValue(l == r))),
-        // Inequality
-        (Value::Integer(l), Value::Integer(r), "!=") => Ok(Value::Boolean(BooleanValue(l != r))),
-        (Value::FloatNumber(l), Value::FloatNumber(r), "!=") => {
-            Ok(Value::Boolean(BooleanValue(l != r)))
-        }
-        (Value::String(l), Value::String(r), "!=") => Ok(Value::Boolean(BooleanValue(l != r))),
-        (Value::Boolean(l), Value::Boolean(r), "!=") => Ok(Value::Boolean(BooleanValue(l != r))),
-        // Comparison
-        (Value::Integer(l), Value::Integer(r), op) if matches!(op, ">" | "<" | ">=" | "<=") => {
-            let res = match op {
-                ">" => l > r,
-                "<" => l < r,
-                ">=" => l >= r,
-                "<=" => l <= r,
-                _ => unreachable!(),
-            };
-            Ok(Value::Boolean(BooleanValue(res)))
-        }
-        (Value::FloatNumber(l), Value::FloatNumber(r), op)
-            if matches!(op, ">" | "<" | ">=" | "<=") =>
-        {
-            let res = match op {
-                ">" => l > r,
-                "<" => l < r,
-                ">=" => l >= r,
-                "<=" => l <= r,
-                _ => unreachable!(),
-            };
-            Ok(Value::Boolean(BooleanValue(res)))
-        }
-        (Value::String(l), Value::String(r), op) if matches!(op, ">" | "<" | ">=" | "<=") => {
-            let res = match op {
-                ">" => l > r,
-                "<" => l < r,
-                ">=" => l >= r,
-                "<=" => l <= r,
-                _ => unreachable!(),
-            };
-            Ok(Value::Boolean(BooleanValue(res)))
-        }
         // Logical
         (Value::Boolean(l), Value::Boolean(r), "&&") => {
             Ok(Value::Boolean(BooleanValue(l.0 && r.0)))
@@ -233,58 +190,140 @@ pub fn apply_operator(left: Value, right: Value, op: &str) -> Result<Value> {
         }
         // Unary
         (Value::Boolean(l), Value::Undefined, "!") => Ok(Value::Boolean(!l)),
-        (Value::Integer(l), Value::Undefined, "-") => Ok(Value::Integer(-l)),
+        (Value::Integer(l), Value::Undefined, "-") => match l.checked_neg() {
+            Some(n) => Ok(Value::Integer(n)),
+            None => integer_overflow(Integer(0), l, "-"),
+        },
         (Value::FloatNumber(l), Value::Undefined, "-") => Ok(Value::FloatNumber(-l)),
-        // Type conversion for comparison (Python allows comparing different numeric types)
-        (Value::Integer(l), Value::FloatNumber(r), op)
-            if matches!(op, "==" | "!=" | ">" | "<" | ">=" | "<=") =>
-        {
-            let left = l.0 as f64;
-            let result = match op {
-                "==" => left == r.0,
-                "!=" => left != r.0,
-                ">" => left > r.0,
-                "<" => left < r.0,
-                ">=" => left >= r.0,
-                "<=" => left <= r.0,
-                _ => unreachable!(),
-            };
-            Ok(Value::Boolean(BooleanValue(result)))
-        }
-        (Value::FloatNumber(l), Value::Integer(r), op)
-            if matches!(op, "==" | "!=" | ">" | "<" | ">=" | "<=") =>
-        {
-            let right = r.0 as f64;
-            let result = match op {
-                "==" => l.0 == right,
-                "!=" => l.0 != right,
-                ">" => l.0 > right,
-                "<" => l.0 < right,
-                ">=" => l.0 >= right,
-                "<=" => l.0 <= right,
-                _ => unreachable!(),
-            };
-            Ok(Value::Boolean(BooleanValue(result)))
-        }
+        // Bitwise operators on integers
+        (Value::Integer(l), Value::Integer(r), "|") => Ok(Value::Integer(Integer(l.0 | r.0))),
+        (Value::Integer(l), Value::Integer(r), "&") => Ok(Value::Integer(Integer(l.0 & r.0))),
+        (Value::Integer(l), Value::Integer(r), "^") => Ok(Value::Integer(Integer(l.0 ^ r.0))),
 
-        // Python-like 'in' operator for arrays and dicts
-        (item, Value::Array(arr), "in") => Ok(Value::Boolean(BooleanValue(arr.contains(&item)))),
-        (Value::String(key), Value::Dict(dict), "in") => {
-            Ok(Value::Boolean(BooleanValue(dict.contains_key(&key.0))))
+        // Set algebra, like Python's set operators
+        (Value::Set(l), Value::Set(r), _) => match op {
+            "|" => Ok(Value::Set(l.union(&r))),
+            "&" => Ok(Value::Set(l.intersection(&r))),
+            "-" => Ok(Value::Set(l.difference(&r))),
+            "^" => Ok(Value::Set(l.symmetric_difference(&r))),
+            _ => Err(miette::miette!(
+                code = "TypeError",
+                "Error: Unsupported operation '{}' between types set and set",
+                op
+            )),
+        },
+
+        // Python-like 'in' operator for arrays, tuples, dicts and sets
+        (item, Value::Array(arr) | Value::Tuple(arr), "in") => {
+            Ok(Value::Boolean(BooleanValue(arr.contains(&item))))
         }
+        (key, Value::Dict(dict), "in") => Ok(Value::Boolean(BooleanValue(dict.contains_key(&key)))),
+        (item, Value::Set(set), "in") => Ok(Value::Boolean(BooleanValue(set.contains(&item)))),
 
-        // Python-like 'not in' operator for arrays and dicts
-        (item, Value::Array(arr), "!in") => Ok(Value::Boolean(BooleanValue(!arr.contains(&item)))),
-        (Value::String(key), Value::Dict(dict), "!in") => {
-            Ok(Value::Boolean(BooleanValue(!dict.contains_key(&key.0))))
+        // Python-like 'not in' operator for arrays, tuples, dicts and sets
+        (item, Value::Array(arr) | Value::Tuple(arr), "!in") => {
+            Ok(Value::Boolean(BooleanValue(!arr.contains(&item))))
+        }
+        (key, Value::Dict(dict), "!in") => {
+            Ok(Value::Boolean(BooleanValue(!dict.contains_key(&key))))
         }
+        (item, Value::Set(set), "!in") => Ok(Value::Boolean(BooleanValue(!set.contains(&item)))),
 
         // Fall through cases
         _ => Err(miette::miette!(
-            "Error: Unsupported operation '{}' between types {:?} and {:?}",
+            code = "TypeError",
+            "Error: Unsupported operation '{}' between types {} and {}",
             op,
-            left,
-            right
+            left.type_name(),
+            right.type_name()
         )),
     }
 }
+
+/// Integer `+`, `-`, `*`, `//` and `%`, reporting overflow instead of panicking or wrapping.
+/// `//` and `%` floor like Python's, so `-7 // 2` is `-4` and `-7 % 2` is `1`.
+/// The divisor has already been checked for zero.
+fn checked_integer_operation(l: Integer, r: Integer, op: &str) -> Result<Value> {
+    let result = match op {
+        "+" => l.checked_add(r),
+        "-" => l.checked_sub(r),
+        "*" => l.checked_mul(r),
+        "//" => l.checked_floor_div(r),
+        _ => l.checked_floor_mod(r),
+    };
+    result
+        .map(|n: Integer| Ok(Value::Integer(n)))
+        .unwrap_or_else(|| integer_overflow(l, r, op))
+}
+
+/// `l ** r` for a non-negative exponent
+fn checked_integer_power(l: Integer, r: Integer) -> Result<Value> {
+    // These bases never grow, so any exponent works, even one too large for `checked_pow`
+    match l.0 {
+        0 | 1 => return Ok(Value::Integer(Integer(if r.0 == 0 { 1 } else { l.0 }))),
+        -1 => return Ok(Value::Integer(Integer(if r.0 % 2 == 0 { 1 } else { -1 }))),
+        _ => {}
+    }
+    u32::try_from(r.0)
+        .ok()
+        .and_then(|exponent: u32| l.0.checked_pow(exponent))
+        .map(|n: i64| Ok(Value::Integer(Integer(n))))
+        .unwrap_or_else(|| integer_overflow(l, r, "**"))
+}
+
+/// With the `bigint` feature an overflowing result is computed exactly instead
+#[cfg(feature = "bigint")]
+fn integer_overflow(l: Integer, r: Integer, op: &str) -> Result<Value> {
+    use num_bigint::BigInt;
+    crate::bigint::integer_operation(BigInt::from(l.0), BigInt::from(r.0), op)
+}
+
+#[cfg(not(feature = "bigint"))]
+fn integer_overflow(l: Integer, r: Integer, op: &str) -> Result<Value> {
+    Err(miette::miette!(
+        code = "OverflowError",
+        "Error: Integer overflow: {} {} {} does not fit in 64 bits.",
+        l,
+        op,
+        r
+    ))
+}
+
+/// A number as a float, for operators that mix ints and floats
+fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Integer(n) => n.0 as f64,
+        Value::FloatNumber(f) => f.0,
+        _ => f64::NAN,
+    }
+}
+
+/// Python's float floor division and modulo (`float_divmod` in CPython), which keeps
+/// `a == b * (a // b) + a % b` as close as floats allow and gives zeros the right sign
+fn float_floor_divmod(a: f64, b: f64) -> (f64, f64) {
+    let mut remainder: f64 = a % b;
+    let mut quotient: f64 = (a - remainder) / b;
+    if remainder != 0.0 {
+        if (b < 0.0) != (remainder < 0.0) {
+            remainder += b;
+            quotient -= 1.0;
+        }
+    } else {
+        remainder = 0.0_f64.copysign(b);
+    }
+    let floor_quotient: f64 = if quotient != 0.0 {
+        let floored: f64 = quotient.floor();
+        if quotient - floored > 0.5 {
+            floored + 1.0
+        } else {
+            floored
+        }
+    } else {
+        0.0_f64.copysign(a / b)
+    };
+    (floor_quotient, remainder)
+}
+
+pub(crate) fn division_by_zero() -> miette::Report {
+    miette::miette!(code = "ZeroDivisionError", "Error: Division by zero.")
+}
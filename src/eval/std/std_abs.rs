@@ -4,10 +4,26 @@ use crate::value::{FloatNumber, Integer, Value};
 /// Returns the absolute value of a number or float
 pub fn std_abs(val: &Value) -> Value {
     match val {
-        Value::Integer(Integer(integer_value)) => Value::Integer(Integer(integer_value.abs())),
+        Value::Integer(Integer(integer_value)) => integer_value
+            .checked_abs()
+            .map(|n: i64| Value::Integer(Integer(n)))
+            .unwrap_or_else(|| overflowed_abs(*integer_value)),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(n) => Value::BigInteger(num_traits::Signed::abs(n)),
         Value::FloatNumber(FloatNumber(float_value)) => {
             Value::FloatNumber(FloatNumber(float_value.abs()))
         }
         _ => Value::Undefined,
     }
 }
+
+/// `abs(i64::MIN)` does not fit in an i64
+#[cfg(feature = "bigint")]
+fn overflowed_abs(n: i64) -> Value {
+    crate::bigint::normalize(-num_bigint::BigInt::from(n))
+}
+
+#[cfg(not(feature = "bigint"))]
+fn overflowed_abs(_: i64) -> Value {
+    Value::Undefined
+}
@@ -0,0 +1,37 @@
+// Handles the TungLang assert() built-in function
+use crate::format::to_str;
+use crate::interpreter::is_truthy;
+use crate::stdlib::StdLib;
+use crate::value::Value;
+
+/// `assert(condition, message?)`: raises an AssertionError when the condition is falsy
+pub fn std_assert(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    if let Some((name, _)) = kwargs.first() {
+        return Err(miette::miette!(
+            code = "TypeError",
+            "Error: assert() got an unexpected keyword argument '{}'.",
+            name
+        ));
+    }
+    match args {
+        [condition] | [condition, _] if is_truthy(condition.clone()) => Ok(Value::Undefined),
+        [_] => Err(miette::miette!(
+            code = "AssertionError",
+            "Error: Assertion failed."
+        )),
+        [_, message] => Err(miette::miette!(
+            code = "AssertionError",
+            "Error: Assertion failed: {}",
+            to_str(message)
+        )),
+        _ => Err(miette::miette!(
+            code = "TypeError",
+            "Error: assert() takes a condition and an optional message, but {} arguments were given.",
+            args.len()
+        )),
+    }
+}
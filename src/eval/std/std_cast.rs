@@ -1,61 +1,62 @@
 // Handles Python-like type conversion functions
+use crate::eval::std::std_math::float_to_integer;
+use crate::format::{to_repr, to_str};
+use crate::stdlib::StdLib;
 use crate::value::{BooleanValue, FloatNumber, Integer, StringValue, Value};
 
 pub fn std_int(val: &Value) -> Value {
     match val {
         Value::Integer(n) => Value::Integer(Integer(n.0)),
-        Value::FloatNumber(f) => Value::Integer(Integer(f.0 as i64)),
-        Value::String(s) => {
-            s.0.parse::<i64>()
-                .map(|n| Value::Integer(Integer(n)))
-                .unwrap_or(Value::Undefined)
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(n) => Value::BigInteger(n.clone()),
+        #[cfg(feature = "bigint")]
+        Value::FloatNumber(f) => crate::bigint::from_f64(f.0).unwrap_or(Value::Undefined),
+        #[cfg(not(feature = "bigint"))]
+        Value::FloatNumber(f) if f.0 >= i64::MIN as f64 && f.0 < -(i64::MIN as f64) => {
+            Value::Integer(Integer(f.0 as i64))
         }
+        #[cfg(not(feature = "bigint"))]
+        Value::FloatNumber(_) => Value::Undefined,
+        #[cfg(feature = "bigint")]
+        Value::String(s) => integer_text(&s.0)
+            .and_then(crate::bigint::parse)
+            .unwrap_or(Value::Undefined),
+        #[cfg(not(feature = "bigint"))]
+        Value::String(s) => integer_text(&s.0)
+            .and_then(|text| text.parse::<i64>().ok())
+            .map(|n| Value::Integer(Integer(n)))
+            .unwrap_or(Value::Undefined),
         Value::Boolean(BooleanValue(true)) => Value::Integer(Integer(1)),
         Value::Boolean(BooleanValue(false)) => Value::Integer(Integer(0)),
-        Value::Array(_) | Value::Dict(_) => Value::Undefined,
-        Value::Function { .. } => Value::Undefined,
+        Value::Array(_) | Value::Tuple(_) | Value::Dict(_) | Value::Set(_) => Value::Undefined,
+        Value::Function { .. } | Value::BuiltinFunction { .. } => Value::Undefined,
+        Value::Struct(_) | Value::StructType { .. } | Value::Module { .. } => Value::Undefined,
         Value::Undefined => Value::Undefined,
     }
 }
 
+/// The text of a string `int()` accepts, with surrounding whitespace removed: an optional sign
+/// followed by decimal digits, the same with or without big integers
+fn integer_text(text: &str) -> Option<&str> {
+    let text: &str = text.trim();
+    let digits: &str = text.strip_prefix(['+', '-']).unwrap_or(text);
+    (!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())).then_some(text)
+}
+
 pub fn std_str(val: &Value) -> Value {
-    match val {
-        Value::String(s) => Value::String(StringValue(s.0.clone())),
-        Value::Integer(n) => Value::String(StringValue(n.to_string())),
-        Value::FloatNumber(f) => Value::String(StringValue(f.to_string())),
-        Value::Boolean(b) => Value::String(StringValue(b.to_string())),
-        Value::Undefined => Value::String(StringValue("undefined".to_string())),
-        Value::Array(arr) => {
-            let items: Vec<String> = arr
-                .iter()
-                .map(|v| match v {
-                    Value::String(s) => format!("\"{}\"", s),
-                    _ => format!("{}", v),
-                })
-                .collect();
-            Value::String(StringValue(format!("[{}]", items.join(", "))))
-        }
-        Value::Dict(map) => {
-            let items: Vec<String> = map
-                .iter()
-                .map(|(k, v)| {
-                    let value_str = match v {
-                        Value::String(s) => format!("\"{}\"", s),
-                        _ => format!("{}", v),
-                    };
-                    format!("\"{}\": {}", k, value_str)
-                })
-                .collect();
-            Value::String(StringValue(format!("{{{}}}", items.join(", "))))
-        }
-        Value::Function { .. } => Value::String(StringValue("<function>".to_string())),
-    }
+    Value::String(StringValue(to_str(val)))
+}
+
+pub fn std_repr(val: &Value) -> Value {
+    Value::String(StringValue(to_repr(val)))
 }
 
 pub fn std_float(val: &Value) -> Value {
     match val {
         Value::FloatNumber(float_value) => Value::FloatNumber(FloatNumber(float_value.0)),
         Value::Integer(integer_value) => Value::FloatNumber(FloatNumber(integer_value.0 as f64)),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(n) => Value::FloatNumber(FloatNumber(crate::bigint::to_f64(n))),
         Value::String(string_value) => string_value
             .0
             .parse::<f64>()
@@ -63,8 +64,9 @@ pub fn std_float(val: &Value) -> Value {
             .unwrap_or(Value::Undefined),
         Value::Boolean(BooleanValue(true)) => Value::FloatNumber(FloatNumber(1.0)),
         Value::Boolean(BooleanValue(false)) => Value::FloatNumber(FloatNumber(0.0)),
-        Value::Function { .. } => Value::Undefined,
-        Value::Array(_) | Value::Dict(_) => Value::Undefined,
+        Value::Function { .. } | Value::BuiltinFunction { .. } => Value::Undefined,
+        Value::Array(_) | Value::Tuple(_) | Value::Dict(_) | Value::Set(_) => Value::Undefined,
+        Value::Struct(_) | Value::StructType { .. } | Value::Module { .. } => Value::Undefined,
         Value::Undefined => Value::Undefined,
     }
 }
@@ -73,11 +75,70 @@ pub fn std_bool(val: &Value) -> Value {
     match val {
         Value::Boolean(boolean_value) => Value::Boolean(BooleanValue(boolean_value.0)),
         Value::Integer(integer_value) => Value::Boolean(BooleanValue(integer_value.0 != 0)),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(_) => Value::Boolean(BooleanValue(true)),
         Value::FloatNumber(float_value) => Value::Boolean(BooleanValue(float_value.0 != 0.0)),
         Value::String(string_value) => Value::Boolean(BooleanValue(!string_value.0.is_empty())),
-        Value::Array(array) => Value::Boolean(BooleanValue(!array.is_empty())),
+        Value::Array(array) | Value::Tuple(array) => {
+            Value::Boolean(BooleanValue(!array.is_empty()))
+        }
         Value::Dict(dictionary) => Value::Boolean(BooleanValue(!dictionary.is_empty())),
+        Value::Set(set) => Value::Boolean(BooleanValue(!set.is_empty())),
         Value::Undefined => Value::Boolean(BooleanValue(false)),
-        Value::Function { .. } => Value::Boolean(BooleanValue(false)),
+        Value::Function { .. } | Value::BuiltinFunction { .. } => {
+            Value::Boolean(BooleanValue(false))
+        }
+        Value::Struct(_) | Value::StructType { .. } | Value::Module { .. } => {
+            Value::Boolean(BooleanValue(true))
+        }
+    }
+}
+
+/// `int(value)` as a built-in: a string that is not a whole number raises a ValueError, and a
+/// float is truncated like `math.trunc`, so NaN, infinities and floats too large for an int
+/// raise an error instead of becoming some other number
+pub fn std_int_call(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    match (args, kwargs) {
+        ([Value::FloatNumber(FloatNumber(f))], []) => float_to_integer("int", args, f.trunc()),
+        _ => checked_cast("int", args, kwargs, std_int),
+    }
+}
+
+/// `float(value)` as a built-in: a string that is not a number raises a ValueError
+pub fn std_float_call(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    checked_cast("float", args, kwargs, std_float)
+}
+
+fn checked_cast(
+    name: &str,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+    cast: fn(&Value) -> Value,
+) -> miette::Result<Value> {
+    if let Some((keyword, _)) = kwargs.first() {
+        return Err(miette::miette!(
+            code = "TypeError",
+            "Error: {}() got an unexpected keyword argument '{}'.",
+            name,
+            keyword
+        ));
+    }
+    let value: &Value = args.first().unwrap_or(&Value::Integer(Integer(0)));
+    match (cast(value), value) {
+        (Value::Undefined, Value::String(text)) => Err(miette::miette!(
+            code = "ValueError",
+            "Error: Invalid literal for {}(): {}.",
+            name,
+            to_repr(&Value::String(text.clone()))
+        )),
+        (result, _) => Ok(result),
     }
 }
@@ -0,0 +1,39 @@
+// Python-like dict functions for TungLang
+use crate::value::Value;
+
+// keys function (returns the keys in insertion order)
+pub fn std_keys(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Dict(dict)) => Value::Array(dict.keys().cloned().collect()),
+        _ => Value::Undefined,
+    }
+}
+
+// values function (returns the values in insertion order)
+pub fn std_values(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Dict(dict)) => Value::Array(dict.values().cloned().collect()),
+        _ => Value::Undefined,
+    }
+}
+
+// items function (returns (key, value) tuples in insertion order)
+pub fn std_items(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Dict(dict)) => Value::Array(
+            dict.iter()
+                .map(|(key, value)| Value::Tuple(vec![key.clone(), value.clone()]))
+                .collect(),
+        ),
+        _ => Value::Undefined,
+    }
+}
+
+// get function (returns the value for a key, or the default when it is missing)
+pub fn std_get(args: &[Value]) -> Value {
+    let default: Value = args.get(2).cloned().unwrap_or(Value::Undefined);
+    match (args.first(), args.get(1)) {
+        (Some(Value::Dict(dict)), Some(key)) => dict.get(key).cloned().unwrap_or(default),
+        _ => Value::Undefined,
+    }
+}
@@ -1,20 +1,6 @@
 // Python-like list functions for TungLang
-use crate::value::{FloatNumber, Integer, Value};
-
-// append function (modifies list in-place like Python's list.append())
-pub fn std_append(args: &[Value]) -> Value {
-    if args.len() < 2 {
-        return Value::Undefined;
-    }
-
-    match args[0].clone() {
-        Value::Array(mut arr) => {
-            arr.push(args[1].clone());
-            Value::Array(arr)
-        }
-        _ => Value::Undefined,
-    }
-}
+use crate::eval::compare::sort_order;
+use crate::value::{Integer, Value};
 
 // insert function (modifies list in-place like Python's list.insert())
 pub fn std_insert(args: &[Value]) -> Value {
@@ -38,46 +24,6 @@ pub fn std_insert(args: &[Value]) -> Value {
     }
 }
 
-// pop function (removes and returns item at index, default is last)
-pub fn std_pop(args: &[Value]) -> Value {
-    if args.is_empty() {
-        return Value::Undefined;
-    }
-
-    match args[0].clone() {
-        Value::Array(mut array) => {
-            if array.is_empty() {
-                return Value::Undefined;
-            }
-
-            let idx = if args.len() > 1 {
-                match &args[1] {
-                    Value::Integer(Integer(index_value)) => {
-                        if *index_value < 0 {
-                            array
-                                .len()
-                                .saturating_sub(index_value.unsigned_abs() as usize)
-                        } else {
-                            *index_value as usize
-                        }
-                    }
-                    _ => array.len() - 1,
-                }
-            } else {
-                array.len() - 1
-            };
-
-            if idx < array.len() {
-                let removed = array.remove(idx);
-                removed
-            } else {
-                Value::Undefined
-            }
-        }
-        _ => Value::Undefined,
-    }
-}
-
 // index function (returns the index of the first occurrence of value)
 pub fn std_index(args: &[Value]) -> Value {
     if args.len() < 2 {
@@ -115,20 +61,7 @@ pub fn std_sort(args: &[Value]) -> Value {
 
     match args[0].clone() {
         Value::Array(mut array) => {
-            array.sort_by(|a, b| match (a, b) {
-                (Value::Integer(Integer(n1)), Value::Integer(Integer(n2))) => n1.cmp(n2),
-                (Value::FloatNumber(FloatNumber(f1)), Value::FloatNumber(FloatNumber(f2))) => {
-                    f1.partial_cmp(f2).unwrap_or(std::cmp::Ordering::Equal)
-                }
-                (Value::Integer(Integer(n)), Value::FloatNumber(FloatNumber(f))) => (*n as f64)
-                    .partial_cmp(f)
-                    .unwrap_or(std::cmp::Ordering::Equal),
-                (Value::FloatNumber(FloatNumber(f)), Value::Integer(Integer(n))) => f
-                    .partial_cmp(&(*n as f64))
-                    .unwrap_or(std::cmp::Ordering::Equal),
-                (Value::String(string1), Value::String(string2)) => string1.cmp(string2),
-                _ => std::cmp::Ordering::Equal,
-            });
+            array.sort_by(sort_order);
             Value::Array(array)
         }
         _ => Value::Undefined,
@@ -1,154 +1,38 @@
 // Python-like math functions for TungLang
-use crate::value::{FloatNumber, Integer, Value};
+use crate::eval::compare::{sort_order, values_equal};
+use crate::eval::operators::{apply_operator, division_by_zero};
+use crate::eval::std::std_abs::std_abs;
+use crate::format::to_repr;
+use crate::stdlib::StdLib;
+use crate::value::{BooleanValue, FloatNumber, Integer, Value};
+use std::cmp::Ordering;
 
-// min function
+// min function: the smallest item of an array, or the smallest argument
 pub fn std_min(args: &[Value]) -> Value {
-    if args.is_empty() {
-        return Value::Undefined;
-    }
-
-    match &args[0] {
-        Value::Array(array) => {
-            if array.is_empty() {
-                return Value::Undefined;
-            }
-
-            let mut min_value = array[0].clone();
-
-            for value in array {
-                match (value, &min_value) {
-                    (Value::Integer(Integer(n1)), Value::Integer(Integer(n2))) if n1 < n2 => {
-                        min_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f1)), Value::FloatNumber(FloatNumber(f2)))
-                        if f1 < f2 =>
-                    {
-                        min_value = value.clone()
-                    }
-                    (Value::Integer(Integer(n)), Value::FloatNumber(FloatNumber(f)))
-                        if (*n as f64) < *f =>
-                    {
-                        min_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f)), Value::Integer(Integer(n)))
-                        if *f < (*n as f64) =>
-                    {
-                        min_value = value.clone()
-                    }
-                    (Value::String(s1), Value::String(s2)) if s1 < s2 => min_value = value.clone(),
-                    _ => {}
-                }
-            }
-
-            min_value
-        }
-        _ => {
-            // Find min among the arguments
-            let mut min_value = args[0].clone();
-
-            for value in args {
-                match (value, &min_value) {
-                    (Value::Integer(Integer(n1)), Value::Integer(Integer(n2))) if n1 < n2 => {
-                        min_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f1)), Value::FloatNumber(FloatNumber(f2)))
-                        if f1 < f2 =>
-                    {
-                        min_value = value.clone()
-                    }
-                    (Value::Integer(Integer(n)), Value::FloatNumber(FloatNumber(f)))
-                        if (*n as f64) < *f =>
-                    {
-                        min_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f)), Value::Integer(Integer(n)))
-                        if *f < (*n as f64) =>
-                    {
-                        min_value = value.clone()
-                    }
-                    (Value::String(s1), Value::String(s2)) if s1 < s2 => min_value = value.clone(),
-                    _ => {}
-                }
-            }
-
-            min_value
-        }
-    }
+    extreme(args, Ordering::Less)
 }
 
-// max function
+// max function: the largest item of an array, or the largest argument
 pub fn std_max(args: &[Value]) -> Value {
-    if args.is_empty() {
-        return Value::Undefined;
-    }
-
-    match &args[0] {
-        Value::Array(array) => {
-            if array.is_empty() {
-                return Value::Undefined;
-            }
-
-            let mut max_value = array[0].clone();
-
-            for value in array {
-                match (value, &max_value) {
-                    (Value::Integer(Integer(n1)), Value::Integer(Integer(n2))) if n1 > n2 => {
-                        max_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f1)), Value::FloatNumber(FloatNumber(f2)))
-                        if f1 > f2 =>
-                    {
-                        max_value = value.clone()
-                    }
-                    (Value::Integer(Integer(n)), Value::FloatNumber(FloatNumber(f)))
-                        if (*n as f64) > *f =>
-                    {
-                        max_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f)), Value::Integer(Integer(n)))
-                        if *f > (*n as f64) =>
-                    {
-                        max_value = value.clone()
-                    }
-                    (Value::String(s1), Value::String(s2)) if s1 > s2 => max_value = value.clone(),
-                    _ => {}
-                }
-            }
-
-            max_value
-        }
-        _ => {
-            // Find max among the arguments
-            let mut max_value = args[0].clone();
-
-            for value in args {
-                match (value, &max_value) {
-                    (Value::Integer(Integer(n1)), Value::Integer(Integer(n2))) if n1 > n2 => {
-                        max_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f1)), Value::FloatNumber(FloatNumber(f2)))
-                        if f1 > f2 =>
-                    {
-                        max_value = value.clone()
-                    }
-                    (Value::Integer(Integer(n)), Value::FloatNumber(FloatNumber(f)))
-                        if (*n as f64) > *f =>
-                    {
-                        max_value = value.clone()
-                    }
-                    (Value::FloatNumber(FloatNumber(f)), Value::Integer(Integer(n)))
-                        if *f > (*n as f64) =>
-                    {
-                        max_value = value.clone()
-                    }
-                    (Value::String(s1), Value::String(s2)) if s1 > s2 => max_value = value.clone(),
-                    _ => {}
-                }
-            }
+    extreme(args, Ordering::Greater)
+}
 
-            max_value
+/// The first value that no other value orders `wanted` of, using the comparison operators' rules
+fn extreme(args: &[Value], wanted: Ordering) -> Value {
+    let values: &[Value] = match args {
+        [Value::Array(array)] => array,
+        _ => args,
+    };
+    let mut values = values.iter();
+    let Some(mut best) = values.next() else {
+        return Value::Undefined;
+    };
+    for value in values {
+        if sort_order(value, best) == wanted {
+            best = value;
         }
     }
+    best.clone()
 }
 
 // sum function
@@ -173,7 +57,13 @@ pub fn std_sum(args: &[Value]) -> Value {
                         if is_float {
                             sum_float += *n as f64;
                         } else {
-                            sum_int += n;
+                            match sum_int.checked_add(*n) {
+                                Some(total) => sum_int = total,
+                                #[cfg(feature = "bigint")]
+                                None => return crate::bigint::sum(array),
+                                #[cfg(not(feature = "bigint"))]
+                                None => return Value::Undefined, // Integer overflow
+                            }
                         }
                     }
                     Value::FloatNumber(FloatNumber(f)) => {
@@ -183,6 +73,8 @@ pub fn std_sum(args: &[Value]) -> Value {
                         }
                         sum_float += f;
                     }
+                    #[cfg(feature = "bigint")]
+                    Value::BigInteger(_) => return crate::bigint::sum(array),
                     _ => return Value::Undefined, // Non-numeric value
                 }
             }
@@ -197,10 +89,15 @@ pub fn std_sum(args: &[Value]) -> Value {
     }
 }
 
-// round function
-pub fn std_round(args: &[Value]) -> Value {
+// round function: to the nearest int, so NaN, infinities and floats too large for an int raise
+// an error, or to `digits` decimal places as a float
+pub fn std_round(
+    _stdlib: &StdLib,
+    args: &[Value],
+    _kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
     if args.is_empty() {
-        return Value::Undefined;
+        return Ok(Value::Undefined);
     }
 
     let value = &args[0];
@@ -214,15 +111,444 @@ pub fn std_round(args: &[Value]) -> Value {
     };
 
     match value {
-        Value::Integer(Integer(n)) => Value::Integer(Integer(*n)),
+        Value::Integer(Integer(n)) => Ok(Value::Integer(Integer(*n))),
         Value::FloatNumber(FloatNumber(f)) => {
             if digits == 0 {
-                Value::Integer(Integer(f.round() as i64))
+                float_to_integer("round", args, f.round())
             } else {
                 let factor = 10.0f64.powi(digits as i32);
-                Value::FloatNumber(FloatNumber((f * factor).round() / factor))
+                Ok(Value::FloatNumber(FloatNumber(
+                    (f * factor).round() / factor,
+                )))
             }
         }
-        _ => Value::Undefined,
+        _ => Ok(Value::Undefined),
+    }
+}
+
+// divmod function (returns (a // b, a % b), with Python's flooring rules)
+pub fn std_divmod(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("divmod", args, kwargs, 2)?;
+    let [a, b] = args else {
+        unreachable!("divmod() takes 2 arguments");
+    };
+    Ok(Value::Tuple(vec![
+        apply_operator(a.clone(), b.clone(), "//")?,
+        apply_operator(a.clone(), b.clone(), "%")?,
+    ]))
+}
+
+/// Rejects keyword arguments, and a number of positional arguments other than `count`
+pub(crate) fn expect_arguments(
+    name: &str,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+    count: usize,
+) -> miette::Result<()> {
+    if let Some((keyword, _)) = kwargs.first() {
+        return Err(unexpected_keyword(name, keyword));
+    }
+    if args.len() != count {
+        return Err(miette::miette!(
+            code = "TypeError",
+            "Error: {}() takes {} argument{} but {} {} given.",
+            name,
+            count,
+            if count == 1 { "" } else { "s" },
+            args.len(),
+            if args.len() == 1 { "was" } else { "were" }
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn unexpected_keyword(name: &str, keyword: &str) -> miette::Report {
+    miette::miette!(
+        code = "TypeError",
+        "Error: {}() got an unexpected keyword argument '{}'.",
+        name,
+        keyword
+    )
+}
+
+/// A number argument as a float, the way the operators promote an int mixed with a float
+fn float_arg(name: &str, value: &Value) -> miette::Result<f64> {
+    match value {
+        Value::Integer(Integer(n)) => Ok(*n as f64),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(n) => Ok(crate::bigint::to_f64(n)),
+        Value::FloatNumber(FloatNumber(f)) => Ok(*f),
+        other => Err(miette::miette!(
+            code = "TypeError",
+            "Error: {}() needs a number, not {}.",
+            name,
+            other.type_name()
+        )),
+    }
+}
+
+/// An integer argument of either size, for `gcd` and `lcm`
+fn integer_arg(name: &str, value: &Value) -> miette::Result<Value> {
+    match value {
+        Value::Integer(_) => Ok(value.clone()),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(_) => Ok(value.clone()),
+        other => Err(miette::miette!(
+            code = "TypeError",
+            "Error: {}() needs integers, not {}.",
+            name,
+            other.type_name()
+        )),
+    }
+}
+
+/// A call such as `sqrt(-1)`, as its error messages show it
+fn call_text(name: &str, args: &[Value]) -> String {
+    let args: Vec<String> = args.iter().map(to_repr).collect();
+    format!("{}({})", name, args.join(", "))
+}
+
+fn domain_error(name: &str, args: &[Value]) -> miette::Report {
+    miette::miette!(
+        code = "ValueError",
+        "Error: Math domain error: {}.",
+        call_text(name, args)
+    )
+}
+
+/// A float result, where an infinite result from finite arguments is an overflow, as in Python
+fn float_result(
+    name: &str,
+    args: &[Value],
+    finite_args: bool,
+    result: f64,
+) -> miette::Result<Value> {
+    if finite_args && result.is_infinite() {
+        return Err(miette::miette!(
+            code = "OverflowError",
+            "Error: Math range error: {} is too large.",
+            call_text(name, args)
+        ));
+    }
+    Ok(Value::FloatNumber(FloatNumber(result)))
+}
+
+/// A float function of one number, like `sqrt`; arguments outside its domain raise a ValueError
+fn float_function(
+    name: &str,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+    in_domain: fn(f64) -> bool,
+    function: fn(f64) -> f64,
+) -> miette::Result<Value> {
+    expect_arguments(name, args, kwargs, 1)?;
+    let x: f64 = float_arg(name, &args[0])?;
+    if !in_domain(x) {
+        return Err(domain_error(name, args));
+    }
+    float_result(name, args, x.is_finite(), function(x))
+}
+
+/// `floor`, `ceil` and `trunc`: an int stays as it is, and a float becomes an int
+fn rounding_function(
+    name: &str,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+    function: fn(f64) -> f64,
+) -> miette::Result<Value> {
+    expect_arguments(name, args, kwargs, 1)?;
+    match &args[0] {
+        Value::Integer(_) => Ok(args[0].clone()),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(_) => Ok(args[0].clone()),
+        value => {
+            let x: f64 = function(float_arg(name, value)?);
+            float_to_integer(name, args, x)
+        }
+    }
+}
+
+/// A whole-number float as an int; NaN and infinities have no int to become
+pub(crate) fn float_to_integer(name: &str, args: &[Value], x: f64) -> miette::Result<Value> {
+    if x.is_nan() {
+        return Err(miette::miette!(
+            code = "ValueError",
+            "Error: Cannot convert NaN to an integer in {}.",
+            call_text(name, args)
+        ));
+    }
+    if x.is_infinite() {
+        return Err(miette::miette!(
+            code = "OverflowError",
+            "Error: Cannot convert infinity to an integer in {}.",
+            call_text(name, args)
+        ));
+    }
+    #[cfg(feature = "bigint")]
+    return Ok(crate::bigint::from_f64(x).expect("finite floats have an integer part"));
+    #[cfg(not(feature = "bigint"))]
+    if x >= i64::MIN as f64 && x < -(i64::MIN as f64) {
+        Ok(Value::Integer(Integer(x as i64)))
+    } else {
+        Err(miette::miette!(
+            code = "OverflowError",
+            "Error: Integer overflow: {} does not fit in 64 bits.",
+            call_text(name, args)
+        ))
+    }
+}
+
+// sqrt function: the square root, as a float
+pub fn std_sqrt(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    float_function("sqrt", args, kwargs, |x| x >= 0.0 || x.is_nan(), f64::sqrt)
+}
+
+// pow function: `x ** y`, but a negative base with a fractional exponent is a domain error
+pub fn std_pow(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("pow", args, kwargs, 2)?;
+    let (x, y) = (float_arg("pow", &args[0])?, float_arg("pow", &args[1])?);
+    match apply_operator(args[0].clone(), args[1].clone(), "**")? {
+        Value::FloatNumber(FloatNumber(result))
+            if result.is_nan() && !x.is_nan() && !y.is_nan() =>
+        {
+            Err(domain_error("pow", args))
+        }
+        Value::FloatNumber(FloatNumber(result)) => {
+            float_result("pow", args, x.is_finite() && y.is_finite(), result)
+        }
+        result => Ok(result),
+    }
+}
+
+// floor function: the largest int not above the number
+pub fn std_floor(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    rounding_function("floor", args, kwargs, f64::floor)
+}
+
+// ceil function: the smallest int not below the number
+pub fn std_ceil(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    rounding_function("ceil", args, kwargs, f64::ceil)
+}
+
+// trunc function: the number without its fractional part, as an int
+pub fn std_trunc(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    rounding_function("trunc", args, kwargs, f64::trunc)
+}
+
+// sin function (radians)
+pub fn std_sin(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    float_function("sin", args, kwargs, |x| !x.is_infinite(), f64::sin)
+}
+
+// cos function (radians)
+pub fn std_cos(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    float_function("cos", args, kwargs, |x| !x.is_infinite(), f64::cos)
+}
+
+// tan function (radians)
+pub fn std_tan(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    float_function("tan", args, kwargs, |x| !x.is_infinite(), f64::tan)
+}
+
+// atan2 function: the angle of the point (x, y), in radians between -pi and pi
+pub fn std_atan2(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("atan2", args, kwargs, 2)?;
+    let (y, x) = (float_arg("atan2", &args[0])?, float_arg("atan2", &args[1])?);
+    Ok(Value::FloatNumber(FloatNumber(y.atan2(x))))
+}
+
+// log function: the natural logarithm, or the logarithm to the given base
+pub fn std_log(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    if args.len() != 2 {
+        return float_function("log", args, kwargs, |x| x > 0.0 || x.is_nan(), f64::ln);
+    }
+    expect_arguments("log", args, kwargs, 2)?;
+    let (x, base) = (float_arg("log", &args[0])?, float_arg("log", &args[1])?);
+    if x <= 0.0 || base <= 0.0 {
+        return Err(domain_error("log", args));
+    }
+    if base == 1.0 {
+        return Err(division_by_zero());
+    }
+    Ok(Value::FloatNumber(FloatNumber(x.ln() / base.ln())))
+}
+
+// log10 function: the base-10 logarithm
+pub fn std_log10(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    float_function("log10", args, kwargs, |x| x > 0.0 || x.is_nan(), f64::log10)
+}
+
+// exp function: e raised to the number
+pub fn std_exp(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    float_function("exp", args, kwargs, |_| true, f64::exp)
+}
+
+// hypot function: the distance from the origin to the point with these coordinates
+pub fn std_hypot(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    if let Some((keyword, _)) = kwargs.first() {
+        return Err(unexpected_keyword("hypot", keyword));
+    }
+    let coordinates: Vec<f64> = args
+        .iter()
+        .map(|value| float_arg("hypot", value))
+        .collect::<miette::Result<_>>()?;
+    let finite: bool = coordinates.iter().all(|x| x.is_finite());
+    let result: f64 = coordinates.into_iter().fold(0.0, f64::hypot);
+    float_result("hypot", args, finite, result)
+}
+
+// gcd function: the greatest common divisor of the integers, 0 for none
+pub fn std_gcd(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    if let Some((keyword, _)) = kwargs.first() {
+        return Err(unexpected_keyword("gcd", keyword));
+    }
+    let mut result: Value = Value::Integer(Integer(0));
+    for value in args {
+        result = gcd(result, integer_arg("gcd", value)?)?;
+    }
+    Ok(result)
+}
+
+// lcm function: the least common multiple of the integers, 1 for none
+pub fn std_lcm(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    if let Some((keyword, _)) = kwargs.first() {
+        return Err(unexpected_keyword("lcm", keyword));
+    }
+    let zero: Value = Value::Integer(Integer(0));
+    let mut result: Value = Value::Integer(Integer(1));
+    for value in args {
+        let value: Value = integer_arg("lcm", value)?;
+        if values_equal(&result, &zero) || values_equal(&value, &zero) {
+            result = zero.clone();
+            continue;
+        }
+        let divisor: Value = gcd(result.clone(), value.clone())?;
+        let quotient: Value = apply_operator(absolute(result)?, divisor, "//")?;
+        result = apply_operator(quotient, absolute(value)?, "*")?;
+    }
+    Ok(result)
+}
+
+/// Euclid's algorithm, through the operators so big integers work too
+fn gcd(a: Value, b: Value) -> miette::Result<Value> {
+    let zero: Value = Value::Integer(Integer(0));
+    let (mut a, mut b) = (absolute(a)?, absolute(b)?);
+    while !values_equal(&b, &zero) {
+        let remainder: Value = apply_operator(a, b.clone(), "%")?;
+        a = b;
+        b = remainder;
+    }
+    Ok(a)
+}
+
+/// The absolute value of an integer, which for the smallest i64 only fits with `bigint`
+fn absolute(n: Value) -> miette::Result<Value> {
+    match std_abs(&n) {
+        Value::Undefined => Err(miette::miette!(
+            code = "OverflowError",
+            "Error: Integer overflow: abs({}) does not fit in 64 bits.",
+            n
+        )),
+        result => Ok(result),
+    }
+}
+
+// isclose function: whether two numbers are equal within a relative or absolute tolerance
+pub fn std_isclose(
+    _stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    let mut rel_tol: f64 = 1e-9;
+    let mut abs_tol: f64 = 0.0;
+    for (keyword, value) in kwargs {
+        match keyword.as_str() {
+            "rel_tol" => rel_tol = float_arg("isclose", value)?,
+            "abs_tol" => abs_tol = float_arg("isclose", value)?,
+            _ => return Err(unexpected_keyword("isclose", keyword)),
+        }
+    }
+    expect_arguments("isclose", args, &[], 2)?;
+    if rel_tol < 0.0 || abs_tol < 0.0 {
+        return Err(miette::miette!(
+            code = "ValueError",
+            "Error: isclose() tolerances must be non-negative."
+        ));
     }
+    let (a, b) = (
+        float_arg("isclose", &args[0])?,
+        float_arg("isclose", &args[1])?,
+    );
+    let close: bool = a == b
+        || (a.is_finite() && b.is_finite() && {
+            let difference: f64 = (a - b).abs();
+            difference <= (rel_tol * b).abs()
+                || difference <= (rel_tol * a).abs()
+                || difference <= abs_tol
+        });
+    Ok(Value::Boolean(BooleanValue(close)))
 }
@@ -0,0 +1,22 @@
+// Operating system functions for TungLang's `os` module
+use crate::value::{StringValue, Value};
+use std::env;
+
+// getenv function: an environment variable's value, or the default (none if not given)
+pub fn std_getenv(args: &[Value]) -> Value {
+    let Some(Value::String(StringValue(name))) = args.first() else {
+        return Value::Undefined;
+    };
+    match env::var(name) {
+        Ok(value) => Value::String(StringValue(value)),
+        Err(_) => args.get(1).cloned().unwrap_or(Value::Undefined),
+    }
+}
+
+// cwd function: the current working directory
+pub fn std_cwd(_args: &[Value]) -> Value {
+    match env::current_dir() {
+        Ok(directory) => Value::String(StringValue(directory.display().to_string())),
+        Err(_) => Value::Undefined,
+    }
+}
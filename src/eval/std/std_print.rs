@@ -1,16 +1,59 @@
-// Handles the TungLang print() built-in function
+// Handles the TungLang print() and eprint() built-in functions
+use crate::format::to_str;
+use crate::stdlib::StdLib;
 use crate::value::Value;
 
-/// Prints a Value to stdout
-pub fn std_print(val: &Value) {
-    match val {
-        Value::String(s) => println!("{}", s),
-        Value::Integer(n) => println!("{}", n),
-        Value::FloatNumber(f) => println!("{}", f),
-        Value::Boolean(b) => println!("{}", b),
-        Value::Array(arr) => println!("{:?}", arr),
-        Value::Dict(map) => println!("{:?}", map),
-        Value::Undefined => println!("undefined"),
-        Value::Function { .. } => println!("<function>"),
+/// Joins the arguments with `sep` and appends `end`, like Python's print()
+fn render(args: &[Value], kwargs: &[(String, Value)], func_name: &str) -> miette::Result<String> {
+    let mut sep: String = " ".to_string();
+    let mut end: String = "\n".to_string();
+    for (name, value) in kwargs {
+        let text: String = match value {
+            Value::String(s) => s.0.clone(),
+            Value::Undefined => continue,
+            other => {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    "Error: '{}' argument of {}() must be a string, not {}.",
+                    name,
+                    func_name,
+                    other.type_name()
+                ))
+            }
+        };
+        match name.as_str() {
+            "sep" => sep = text,
+            "end" => end = text,
+            _ => {
+                return Err(miette::miette!(
+                    code = "TypeError",
+                    "Error: {}() got an unexpected keyword argument '{}'.",
+                    func_name,
+                    name
+                ))
+            }
+        }
     }
+    let parts: Vec<String> = args.iter().map(to_str).collect();
+    Ok(parts.join(&sep) + &end)
+}
+
+/// Prints values to the standard library's output stream
+pub fn std_print(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    stdlib.write_output(&render(args, kwargs, "print")?)?;
+    Ok(Value::Undefined)
+}
+
+/// Prints values to the standard library's error stream
+pub fn std_eprint(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    stdlib.write_error_output(&render(args, kwargs, "eprint")?)?;
+    Ok(Value::Undefined)
 }
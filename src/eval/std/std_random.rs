@@ -0,0 +1,208 @@
+// Pseudo-random numbers for TungLang's `random` module
+use crate::eval::std::std_math::expect_arguments;
+use crate::stdlib::StdLib;
+use crate::value::{FloatNumber, Integer, StringValue, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A deterministic generator (SplitMix64): the same seed always gives the same numbers, on
+/// every platform, so a seeded program prints the same output on every run
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A generator seeded from the clock, for programs that do not choose a seed
+    pub fn from_time() -> Self {
+        let nanos: u128 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        Self::new(nanos as u64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z: u64 = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A float in `[0, 1)`, from the top 53 bits so every value is equally likely
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// An integer in `[0, n)`; draws that would favour small values are rejected
+    pub fn below(&mut self, n: u64) -> u64 {
+        let zone: u64 = u64::MAX - u64::MAX % n;
+        loop {
+            let draw: u64 = self.next_u64();
+            if draw < zone {
+                return draw % n;
+            }
+        }
+    }
+
+    /// Shuffles items in place (Fisher-Yates); with `count`, only the first `count` positions
+    /// get a random item, which is all `sample` needs
+    fn shuffle<T>(&mut self, items: &mut [T], count: usize) {
+        for i in 0..count.min(items.len().saturating_sub(1)) {
+            let j: usize = i + self.below((items.len() - i) as u64) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+fn integer_arg(name: &str, value: &Value) -> miette::Result<i64> {
+    match value {
+        Value::Integer(Integer(n)) => Ok(*n),
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(_) => Err(miette::miette!(
+            code = "OverflowError",
+            "Error: {}() needs integers that fit in 64 bits.",
+            name
+        )),
+        other => Err(miette::miette!(
+            code = "TypeError",
+            "Error: {}() needs integers, not {}.",
+            name,
+            other.type_name()
+        )),
+    }
+}
+
+/// The items of a sequence argument: an array's or tuple's items, or a string's characters
+fn sequence_arg(name: &str, value: &Value) -> miette::Result<Vec<Value>> {
+    match value {
+        Value::Array(items) | Value::Tuple(items) => Ok(items.clone()),
+        Value::String(StringValue(text)) => Ok(text
+            .chars()
+            .map(|c| Value::String(StringValue(c.to_string())))
+            .collect()),
+        other => Err(miette::miette!(
+            code = "TypeError",
+            "Error: {}() needs an array, tuple or string, not {}.",
+            name,
+            other.type_name()
+        )),
+    }
+}
+
+// seed function: restarts the generator, so the numbers after it repeat on every run;
+// without an argument it reseeds from the clock
+pub fn std_seed(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    let rng: Rng = match args.first() {
+        None | Some(Value::Undefined) => {
+            expect_arguments("seed", &[], kwargs, 0)?;
+            Rng::from_time()
+        }
+        Some(value) => {
+            expect_arguments("seed", args, kwargs, 1)?;
+            Rng::new(integer_arg("seed", value)? as u64)
+        }
+    };
+    *stdlib.rng() = rng;
+    Ok(Value::Undefined)
+}
+
+// random function: a float from 0 up to, but not including, 1
+pub fn std_random(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("random", args, kwargs, 0)?;
+    Ok(Value::FloatNumber(FloatNumber(stdlib.rng().next_f64())))
+}
+
+// randint function: an int from a to b, both included
+pub fn std_randint(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("randint", args, kwargs, 2)?;
+    let (low, high) = (
+        integer_arg("randint", &args[0])?,
+        integer_arg("randint", &args[1])?,
+    );
+    if low > high {
+        return Err(miette::miette!(
+            code = "ValueError",
+            "Error: randint() needs a <= b, not randint({}, {}).",
+            low,
+            high
+        ));
+    }
+    // The width of the full i64 range does not fit in a u64, but then any draw will do
+    let width: u128 = (high as i128 - low as i128 + 1) as u128;
+    let offset: u64 = match u64::try_from(width) {
+        Ok(width) => stdlib.rng().below(width),
+        Err(_) => stdlib.rng().next_u64(),
+    };
+    Ok(Value::Integer(Integer(low.wrapping_add(offset as i64))))
+}
+
+// choice function: a random item of a non-empty sequence
+pub fn std_choice(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("choice", args, kwargs, 1)?;
+    let mut items: Vec<Value> = sequence_arg("choice", &args[0])?;
+    if items.is_empty() {
+        return Err(miette::miette!(
+            code = "IndexError",
+            "Error: Cannot choose from an empty sequence."
+        ));
+    }
+    let index: usize = stdlib.rng().below(items.len() as u64) as usize;
+    Ok(items.swap_remove(index))
+}
+
+// shuffle function: a copy of the sequence's items in random order, like `sort` for sorting
+pub fn std_shuffle(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("shuffle", args, kwargs, 1)?;
+    let mut items: Vec<Value> = sequence_arg("shuffle", &args[0])?;
+    let count: usize = items.len();
+    stdlib.rng().shuffle(&mut items, count);
+    Ok(Value::Array(items))
+}
+
+// sample function: k different items of the sequence, in random order
+pub fn std_sample(
+    stdlib: &StdLib,
+    args: &[Value],
+    kwargs: &[(String, Value)],
+) -> miette::Result<Value> {
+    expect_arguments("sample", args, kwargs, 2)?;
+    let mut items: Vec<Value> = sequence_arg("sample", &args[0])?;
+    let count: i64 = integer_arg("sample", &args[1])?;
+    if count < 0 || count as u64 > items.len() as u64 {
+        return Err(miette::miette!(
+            code = "ValueError",
+            "Error: Cannot sample {} items from a sequence of {}.",
+            count,
+            items.len()
+        ));
+    }
+    let count: usize = count as usize;
+    stdlib.rng().shuffle(&mut items, count);
+    items.truncate(count);
+    Ok(Value::Array(items))
+}
@@ -0,0 +1,46 @@
+// Python-like set functions for TungLang
+use crate::value::{SetValue, StringValue, Value};
+
+// set function (builds a set from an array, tuple, set, string or dict keys; set() is empty)
+pub fn std_set(args: &[Value]) -> Value {
+    let items: Vec<Value> = match args.first() {
+        None => Vec::new(),
+        Some(Value::Array(items) | Value::Tuple(items)) => items.clone(),
+        Some(Value::Set(set)) => return Value::Set(set.clone()),
+        Some(Value::String(s)) => {
+            s.0.chars()
+                .map(|c: char| Value::String(StringValue(c.to_string())))
+                .collect()
+        }
+        Some(Value::Dict(dict)) => dict.keys().cloned().collect(),
+        Some(_) => return Value::Undefined,
+    };
+    if !items.iter().all(Value::is_hashable) {
+        return Value::Undefined;
+    }
+    Value::Set(items.into_iter().collect::<SetValue>())
+}
+
+// add function (returns the set with the item added)
+pub fn std_add(args: &[Value]) -> Value {
+    match (args.first(), args.get(1)) {
+        (Some(Value::Set(set)), Some(item)) if item.is_hashable() => {
+            let mut set: SetValue = set.clone();
+            set.insert(item.clone());
+            Value::Set(set)
+        }
+        _ => Value::Undefined,
+    }
+}
+
+// remove function for sets (returns the set without the item; missing items are ignored)
+pub fn std_remove(args: &[Value]) -> Value {
+    match (args.first(), args.get(1)) {
+        (Some(Value::Set(set)), Some(item)) => {
+            let mut set: SetValue = set.clone();
+            set.remove(item);
+            Value::Set(set)
+        }
+        _ => Value::Undefined,
+    }
+}
@@ -0,0 +1,258 @@
+// Python-like string functions for TungLang
+use crate::format::format_value;
+use crate::value::{BooleanValue, Integer, StringValue, Value};
+
+fn string_arg(args: &[Value], index: usize) -> Option<&str> {
+    match args.get(index) {
+        Some(Value::String(StringValue(s))) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+fn strings(parts: Vec<&str>) -> Value {
+    Value::Array(
+        parts
+            .into_iter()
+            .map(|part: &str| Value::String(StringValue(part.to_string())))
+            .collect(),
+    )
+}
+
+// split function (splits on whitespace by default, or on a separator)
+pub fn std_split(args: &[Value]) -> Value {
+    let Some(s) = string_arg(args, 0) else {
+        return Value::Undefined;
+    };
+    match args.get(1) {
+        None | Some(Value::Undefined) => strings(s.split_whitespace().collect()),
+        Some(Value::String(StringValue(sep))) if !sep.is_empty() => match args.get(2) {
+            Some(Value::Integer(Integer(max_split))) if *max_split >= 0 => {
+                strings(s.splitn(*max_split as usize + 1, sep.as_str()).collect())
+            }
+            _ => strings(s.split(sep.as_str()).collect()),
+        },
+        _ => Value::Undefined,
+    }
+}
+
+// join function (joins an array of values with the separator: join(", ", items))
+pub fn std_join(args: &[Value]) -> Value {
+    match (string_arg(args, 0), args.get(1)) {
+        (Some(sep), Some(Value::Array(items))) => {
+            let parts: Vec<String> = items.iter().map(|item: &Value| item.to_string()).collect();
+            Value::String(StringValue(parts.join(sep)))
+        }
+        _ => Value::Undefined,
+    }
+}
+
+fn strip_with(args: &[Value], strip: fn(&str, &[char]) -> String) -> Value {
+    let Some(s) = string_arg(args, 0) else {
+        return Value::Undefined;
+    };
+    let chars: Vec<char> = match string_arg(args, 1) {
+        Some(chars) => chars.chars().collect(),
+        None => vec![' ', '\t', '\n', '\r'],
+    };
+    Value::String(StringValue(strip(s, &chars)))
+}
+
+// strip function (removes leading and trailing whitespace, or the given characters)
+pub fn std_strip(args: &[Value]) -> Value {
+    strip_with(args, |s: &str, chars: &[char]| {
+        s.trim_matches(chars).to_string()
+    })
+}
+
+// lstrip function (removes leading whitespace, or the given characters)
+pub fn std_lstrip(args: &[Value]) -> Value {
+    strip_with(args, |s: &str, chars: &[char]| {
+        s.trim_start_matches(chars).to_string()
+    })
+}
+
+// rstrip function (removes trailing whitespace, or the given characters)
+pub fn std_rstrip(args: &[Value]) -> Value {
+    strip_with(args, |s: &str, chars: &[char]| {
+        s.trim_end_matches(chars).to_string()
+    })
+}
+
+// replace function (replaces every occurrence, or the first `count` occurrences)
+pub fn std_replace(args: &[Value]) -> Value {
+    match (
+        string_arg(args, 0),
+        string_arg(args, 1),
+        string_arg(args, 2),
+    ) {
+        (Some(s), Some(old), Some(new)) => match args.get(3) {
+            Some(Value::Integer(Integer(count))) if *count >= 0 => {
+                Value::String(StringValue(s.replacen(old, new, *count as usize)))
+            }
+            _ => Value::String(StringValue(s.replace(old, new))),
+        },
+        _ => Value::Undefined,
+    }
+}
+
+// upper function
+pub fn std_upper(args: &[Value]) -> Value {
+    match string_arg(args, 0) {
+        Some(s) => Value::String(StringValue(s.to_uppercase())),
+        None => Value::Undefined,
+    }
+}
+
+// lower function
+pub fn std_lower(args: &[Value]) -> Value {
+    match string_arg(args, 0) {
+        Some(s) => Value::String(StringValue(s.to_lowercase())),
+        None => Value::Undefined,
+    }
+}
+
+// startswith function
+pub fn std_startswith(args: &[Value]) -> Value {
+    match (string_arg(args, 0), string_arg(args, 1)) {
+        (Some(s), Some(prefix)) => Value::Boolean(BooleanValue(s.starts_with(prefix))),
+        _ => Value::Undefined,
+    }
+}
+
+// endswith function
+pub fn std_endswith(args: &[Value]) -> Value {
+    match (string_arg(args, 0), string_arg(args, 1)) {
+        (Some(s), Some(suffix)) => Value::Boolean(BooleanValue(s.ends_with(suffix))),
+        _ => Value::Undefined,
+    }
+}
+
+// find function (returns the character index of the first occurrence, or -1)
+pub fn std_find(args: &[Value]) -> Value {
+    match (string_arg(args, 0), string_arg(args, 1)) {
+        (Some(s), Some(sub)) => match s.find(sub) {
+            Some(byte_index) => Value::Integer(Integer(s[..byte_index].chars().count() as i64)),
+            None => Value::Integer(Integer(-1)),
+        },
+        _ => Value::Undefined,
+    }
+}
+
+// count function (counts non-overlapping occurrences of a substring)
+pub fn std_count(args: &[Value]) -> Value {
+    match (string_arg(args, 0), string_arg(args, 1)) {
+        (Some(s), Some("")) => Value::Integer(Integer(s.chars().count() as i64 + 1)),
+        (Some(s), Some(sub)) => Value::Integer(Integer(s.matches(sub).count() as i64)),
+        _ => Value::Undefined,
+    }
+}
+
+// isdigit function (true for a non-empty string of digits)
+pub fn std_isdigit(args: &[Value]) -> Value {
+    match string_arg(args, 0) {
+        Some(s) => Value::Boolean(BooleanValue(
+            !s.is_empty() && s.chars().all(|c: char| c.is_ascii_digit()),
+        )),
+        None => Value::Undefined,
+    }
+}
+
+// isalpha function (true for a non-empty string of letters)
+pub fn std_isalpha(args: &[Value]) -> Value {
+    match string_arg(args, 0) {
+        Some(s) => Value::Boolean(BooleanValue(
+            !s.is_empty() && s.chars().all(char::is_alphabetic),
+        )),
+        None => Value::Undefined,
+    }
+}
+
+// chars function (splits a string into an array of single characters)
+pub fn std_chars(args: &[Value]) -> Value {
+    match string_arg(args, 0) {
+        Some(s) => Value::Array(
+            s.chars()
+                .map(|c: char| Value::String(StringValue(c.to_string())))
+                .collect(),
+        ),
+        None => Value::Undefined,
+    }
+}
+
+// format function (fills `{}` fields with the arguments in order, `{0}` with the argument at
+// that position, and `{:spec}` with the format specs of f-strings; `{{` and `}}` are braces)
+pub fn std_format(args: &[Value]) -> miette::Result<Value> {
+    let Some(template) = string_arg(args, 0) else {
+        return Ok(Value::Undefined);
+    };
+    let values: &[Value] = &args[1..];
+    let mut text: String = String::with_capacity(template.len());
+    let mut next: Option<usize> = Some(0);
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut field: String = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => field.push(c),
+                        None => {
+                            return Err(format_error("format() found a '{' that is never closed"))
+                        }
+                    }
+                }
+                let (position, spec) = field.split_once(':').unwrap_or((&field, ""));
+                let index: usize = if position.is_empty() {
+                    let index: usize = next.ok_or_else(|| format_error(MIXED_FIELDS))?;
+                    next = Some(index + 1);
+                    index
+                } else {
+                    if next.is_some_and(|next| next > 0) {
+                        return Err(format_error(MIXED_FIELDS));
+                    }
+                    next = None;
+                    position.parse().map_err(|_| {
+                        format_error(&format!(
+                            "format() field '{{{}}}' is not a number",
+                            position
+                        ))
+                    })?
+                };
+                let Some(value) = values.get(index) else {
+                    return Err(miette::miette!(
+                        code = "IndexError",
+                        "Error: format() has no argument {} for '{{{}}}'.",
+                        index,
+                        field
+                    ));
+                };
+                let formatted: String = format_value(value, spec).map_err(|message| {
+                    format_error(&format!("Invalid format spec: {}", message))
+                })?;
+                text.push_str(&formatted);
+            }
+            '}' => {
+                return Err(format_error(
+                    "format() found a single '}'; write '}}' for a brace",
+                ))
+            }
+            c => text.push(c),
+        }
+    }
+    Ok(Value::String(StringValue(text)))
+}
+
+const MIXED_FIELDS: &str = "format() cannot mix '{}' with numbered fields such as '{0}'";
+
+fn format_error(message: &str) -> miette::Report {
+    miette::miette!(code = "ValueError", "Error: {}.", message)
+}
@@ -0,0 +1,141 @@
+// Decodes TungLang string literals: quotes, raw strings and escape sequences
+use crate::parser::Rule;
+use miette::LabeledSpan;
+use pest::iterators::Pairs;
+
+/// An invalid escape sequence, located by byte offsets within the decoded text's literal
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscapeError {
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+/// Strips the quotes from a string literal and decodes its escape sequences.
+/// Raw strings (`r"..."`) are returned exactly as written.
+pub fn decode_string_literal(literal: &str) -> Result<String, EscapeError> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        return Ok(raw[1..raw.len() - 1].to_string());
+    }
+    let quote_len: usize = if literal.starts_with("\"\"\"") || literal.starts_with("'''") {
+        3
+    } else {
+        1
+    };
+    decode_escapes(&literal[quote_len..literal.len() - quote_len], quote_len)
+}
+
+/// Decodes `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'` and `\u{XXXX}`.
+/// `offset` is added to the positions reported in errors.
+pub fn decode_escapes(text: &str, offset: usize) -> Result<String, EscapeError> {
+    decode(text, offset, false)
+}
+
+/// Decodes the literal text of an f-string: its escapes, and `{{` and `}}` for single braces.
+/// A brace written as an escape, like `\u{7B}`, stays a brace of its own.
+pub fn decode_fstring_text(text: &str) -> Result<String, EscapeError> {
+    decode(text, 0, true)
+}
+
+fn decode(text: &str, offset: usize, doubled_braces: bool) -> Result<String, EscapeError> {
+    let mut decoded: String = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if doubled_braces && (c == '{' || c == '}') {
+            // The grammar only lets braces into f-string text in pairs
+            chars.next();
+            decoded.push(c);
+            continue;
+        }
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        let Some((escape_at, escape)) = chars.next() else {
+            return Err(EscapeError {
+                start: offset + start,
+                end: offset + start + 1,
+                message: "Unterminated escape sequence".to_string(),
+            });
+        };
+        let end: usize = escape_at + escape.len_utf8();
+        match escape {
+            'n' => decoded.push('\n'),
+            't' => decoded.push('\t'),
+            'r' => decoded.push('\r'),
+            '0' => decoded.push('\0'),
+            '\\' => decoded.push('\\'),
+            '"' => decoded.push('"'),
+            '\'' => decoded.push('\''),
+            'u' => {
+                let mut digits: String = String::new();
+                let mut closed: bool = false;
+                let mut end: usize = end;
+                if chars.peek().map(|(_, c)| *c) == Some('{') {
+                    chars.next();
+                    end += 1;
+                    for (i, c) in chars.by_ref() {
+                        end = i + c.len_utf8();
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        digits.push(c);
+                    }
+                }
+                let code_point: Option<char> = if closed && (1..=6).contains(&digits.len()) {
+                    u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                } else {
+                    None
+                };
+                match code_point {
+                    Some(c) => decoded.push(c),
+                    None => {
+                        return Err(EscapeError {
+                            start: offset + start,
+                            end: offset + end,
+                            message: "Invalid unicode escape, expected \\u{XXXX} with 1 to 6 hex digits naming a valid character".to_string(),
+                        })
+                    }
+                }
+            }
+            other => {
+                return Err(EscapeError {
+                    start: offset + start,
+                    end: offset + end,
+                    message: format!("Unknown escape sequence '\\{}'", other),
+                })
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+/// Checks the escapes of every string literal and f-string text in a parsed program, so an
+/// invalid one is reported before the program runs
+pub fn check_escapes(program: Pairs<'static, Rule>) -> miette::Result<()> {
+    for pair in program.flatten() {
+        let decoded: Result<String, EscapeError> = match pair.as_rule() {
+            Rule::string => decode_string_literal(pair.as_str()),
+            Rule::fstring_text => decode_fstring_text(pair.as_str()),
+            _ => continue,
+        };
+        decoded.map_err(|e: EscapeError| escape_error(e, pair.as_span().start()))?;
+    }
+    Ok(())
+}
+
+/// Turns an invalid escape into a diagnostic; `base` is where the literal starts in the source
+pub fn escape_error(error: EscapeError, base: usize) -> miette::Report {
+    miette::miette!(
+        code = "SyntaxError",
+        labels = vec![LabeledSpan::at(
+            base + error.start..base + error.end,
+            "invalid escape"
+        )],
+        "Error: {}.",
+        error.message
+    )
+}
@@ -0,0 +1,168 @@
+// Handles struct construction, field access and method calls for TungLang
+use crate::eval::functions::{bind_arguments, invoke_function, CallArguments};
+use crate::stdlib::StdLib;
+use crate::value::{Dict, MethodTable, Parameter, StructValue, Value};
+use miette::LabeledSpan;
+use pest::Span;
+
+/// Builds a struct instance from constructor arguments, e.g. `Point(1, 2)` or `Point(x = 1, y = 2)`
+pub fn construct_struct(
+    name: &str,
+    fields: &[String],
+    methods: &MethodTable,
+    arguments: CallArguments,
+    call_span: Span,
+) -> miette::Result<Value> {
+    let parameters: Vec<Parameter> = fields
+        .iter()
+        .map(|field: &String| Parameter {
+            name: field.clone(),
+            default: None,
+            variadic: false,
+        })
+        .collect();
+    let fields: Vec<(String, Value)> = bind_arguments(name, &parameters, arguments, call_span)?;
+    Ok(Value::Struct(StructValue {
+        name: name.to_string(),
+        fields,
+        methods: methods.clone(),
+    }))
+}
+
+/// Reads a field from a struct instance, or a name from a module
+pub fn get_field(value: &Value, field: &str, span: Span) -> miette::Result<Value> {
+    let label = || vec![LabeledSpan::at(span.start()..span.end(), "this field")];
+    match value {
+        Value::Struct(struct_value) => match struct_value.get(field) {
+            Some(field_value) => Ok(field_value.clone()),
+            None => Err(miette::miette!(
+                code = "AttributeError",
+                labels = label(),
+                "Error: Struct '{}' has no field '{}'.",
+                struct_value.name,
+                field
+            )),
+        },
+        Value::Module { name, members } => match members.get(field) {
+            Some(member) => Ok(member.clone()),
+            None => Err(miette::miette!(
+                code = "AttributeError",
+                labels = label(),
+                "Error: Module '{}' has no name '{}'.",
+                name,
+                field
+            )),
+        },
+        _ => Err(miette::miette!(
+            code = "TypeError",
+            labels = label(),
+            "Error: Cannot access field '{}' on {}.",
+            field,
+            value
+        )),
+    }
+}
+
+/// Replaces the value found by following `path` through nested struct fields
+pub fn set_field_path(target: &mut Value, path: &[String], value: Value) -> miette::Result<()> {
+    let Some((field, rest)) = path.split_first() else {
+        *target = value;
+        return Ok(());
+    };
+    match target {
+        Value::Struct(struct_value) => {
+            let struct_name: String = struct_value.name.clone();
+            match struct_value.get_mut(field) {
+                Some(field_value) => set_field_path(field_value, rest, value),
+                None => Err(miette::miette!(
+                    code = "AttributeError",
+                    "Error: Struct '{}' has no field '{}'.",
+                    struct_name,
+                    field
+                )),
+            }
+        }
+        _ => Err(miette::miette!(
+            code = "TypeError",
+            "Error: Cannot assign field '{}' on {}.",
+            field,
+            target
+        )),
+    }
+}
+
+/// Assigns to `name.field.field...`, requiring the variable to already exist
+pub fn assign_path(
+    variables: &mut Dict,
+    name: &str,
+    path: &[String],
+    value: Value,
+) -> miette::Result<()> {
+    match variables.get_mut(name) {
+        Some(target) => set_field_path(target, path, value),
+        None => Err(miette::miette!(
+            code = "NameError",
+            "Assignment to undefined variable '{}'.",
+            name
+        )),
+    }
+}
+
+/// Calls a struct method, returning the result and the receiver as the method left it.
+///
+/// Instance methods take the receiver as their first parameter (`self`); methods looked up on
+/// the struct type itself, e.g. `Point.origin()`, are called without one.
+pub fn call_struct_method(
+    receiver: Value,
+    method_name: &str,
+    mut arguments: CallArguments,
+    call_span: Span,
+    stdlib: &StdLib,
+) -> miette::Result<(Value, Option<Value>)> {
+    let label = || {
+        vec![LabeledSpan::at(
+            call_span.start()..call_span.end(),
+            "in this call",
+        )]
+    };
+    let (struct_name, methods, is_instance): (&str, &MethodTable, bool) = match &receiver {
+        Value::Struct(struct_value) => (&struct_value.name, &struct_value.methods, true),
+        Value::StructType { name, methods, .. } => (name, methods, false),
+        _ => {
+            return Err(miette::miette!(
+                code = "AttributeError",
+                labels = label(),
+                "Error: {} has no method '{}'.",
+                receiver,
+                method_name
+            ))
+        }
+    };
+    let Some(method) = methods.get(method_name) else {
+        return Err(miette::miette!(
+            code = "AttributeError",
+            labels = label(),
+            "Error: Struct '{}' has no method '{}'.",
+            struct_name,
+            method_name
+        ));
+    };
+    if !is_instance {
+        let (result, _) =
+            invoke_function(method_name, &method, None, arguments, call_span, stdlib)?;
+        return Ok((result, None));
+    }
+    let self_name: Option<String> = match &method {
+        Value::Function { parameters, .. } => {
+            parameters.first().map(|p: &Parameter| p.name.clone())
+        }
+        _ => None,
+    };
+    arguments.positional.insert(0, receiver);
+    let (result, mut local_vars) =
+        invoke_function(method_name, &method, None, arguments, call_span, stdlib)?;
+    Ok((
+        result,
+        self_name.and_then(|name: String| local_vars.remove(&name)),
+    ))
+}
@@ -0,0 +1,348 @@
+// Turns TungLang values into text: `str` for printing, `repr` for unambiguous output and
+// format specs for f-string fields, e.g. f"{price:>8.2f}"
+use crate::value::{FloatNumber, Integer, Value};
+use std::fmt::Write;
+
+/// How deep nested arrays, dicts and structs are printed before being shown as `...`
+const MAX_DEPTH: usize = 64;
+
+/// The `str()` form: strings are shown as-is, everything else as its `repr()`
+pub fn to_str(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.0.clone(),
+        _ => to_repr(value),
+    }
+}
+
+/// The `repr()` form: strings are quoted and escaped, so `"1"` and `1` print differently.
+/// Items inside arrays, dicts and structs are always shown in this form.
+pub fn to_repr(value: &Value) -> String {
+    let mut out: String = String::new();
+    write_repr(&mut out, value, 0);
+    out
+}
+
+/// A parsed format spec: [[fill]align][sign][0][width][.precision][type]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatSpec {
+    pub fill: Option<char>,
+    pub align: Option<char>,
+    pub sign: bool,
+    pub zero_pad: bool,
+    pub width: usize,
+    pub precision: Option<usize>,
+    pub kind: Option<char>,
+}
+
+impl FormatSpec {
+    pub fn parse(spec: &str) -> Result<FormatSpec, String> {
+        let chars: Vec<char> = spec.chars().collect();
+        let mut result: FormatSpec = FormatSpec::default();
+        let mut i: usize = 0;
+        let is_align = |c: &char| matches!(c, '<' | '>' | '^');
+
+        if chars.len() >= 2 && is_align(&chars[1]) {
+            result.fill = Some(chars[0]);
+            result.align = Some(chars[1]);
+            i = 2;
+        } else if chars.first().is_some_and(is_align) {
+            result.align = Some(chars[0]);
+            i = 1;
+        }
+        if chars.get(i) == Some(&'+') {
+            result.sign = true;
+            i += 1;
+        }
+        if chars.get(i) == Some(&'0') {
+            result.zero_pad = true;
+            i += 1;
+        }
+        let width_start: usize = i;
+        while chars.get(i).is_some_and(char::is_ascii_digit) {
+            i += 1;
+        }
+        if i > width_start {
+            result.width = chars[width_start..i]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .map_err(|_| format!("width in '{}' is too large", spec))?;
+        }
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            let precision_start: usize = i;
+            while chars.get(i).is_some_and(char::is_ascii_digit) {
+                i += 1;
+            }
+            if i == precision_start {
+                return Err(format!("missing precision after '.' in '{}'", spec));
+            }
+            result.precision = Some(
+                chars[precision_start..i]
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .map_err(|_| format!("precision in '{}' is too large", spec))?,
+            );
+        }
+        if let Some(kind) = chars.get(i) {
+            if !matches!(
+                kind,
+                'd' | 'f' | 'e' | 'E' | 'x' | 'X' | 'o' | 'b' | '%' | 's'
+            ) {
+                return Err(format!("unknown format type '{}'", kind));
+            }
+            result.kind = Some(*kind);
+            i += 1;
+        }
+        if i < chars.len() {
+            return Err(format!("invalid format spec '{}'", spec));
+        }
+        Ok(result)
+    }
+}
+
+/// Formats a value according to a format spec string
+pub fn format_value(value: &Value, spec: &str) -> Result<String, String> {
+    let spec: FormatSpec = FormatSpec::parse(spec)?;
+    let is_number: bool = matches!(value, Value::Integer(_) | Value::FloatNumber(_));
+    #[cfg(feature = "bigint")]
+    let is_number: bool = is_number || matches!(value, Value::BigInteger(_));
+    // `s` shows any value, numbers included, as its `str()` text and pads it like text
+    let is_number: bool = is_number && spec.kind != Some('s');
+    let body: String = match (value, spec.kind) {
+        (_, Some('s')) => truncate(value.to_string(), &spec),
+        #[cfg(feature = "bigint")]
+        (Value::BigInteger(n), Some('x')) => format!("{:x}", n),
+        #[cfg(feature = "bigint")]
+        (Value::BigInteger(n), Some('X')) => format!("{:X}", n),
+        #[cfg(feature = "bigint")]
+        (Value::BigInteger(n), Some('o')) => format!("{:o}", n),
+        #[cfg(feature = "bigint")]
+        (Value::BigInteger(n), Some('b')) => format!("{:b}", n),
+        #[cfg(feature = "bigint")]
+        (Value::BigInteger(n), None | Some('d')) if spec.precision.is_none() => n.to_string(),
+        #[cfg(feature = "bigint")]
+        (Value::BigInteger(n), _) => format_float(crate::bigint::to_f64(n), &spec),
+        (Value::Integer(Integer(n)), Some('x')) => format!("{:x}", n),
+        (Value::Integer(Integer(n)), Some('X')) => format!("{:X}", n),
+        (Value::Integer(Integer(n)), Some('o')) => format!("{:o}", n),
+        (Value::Integer(Integer(n)), Some('b')) => format!("{:b}", n),
+        (Value::Integer(Integer(n)), None | Some('d')) if spec.precision.is_none() => n.to_string(),
+        (Value::Integer(Integer(n)), _) => format_float(*n as f64, &spec),
+        (Value::FloatNumber(FloatNumber(f)), Some('d')) => {
+            return Err(format!("cannot format float {} as an integer", f))
+        }
+        (Value::FloatNumber(FloatNumber(f)), _) => format_float(*f, &spec),
+        (_, None) => truncate(value.to_string(), &spec),
+        (_, Some(kind)) => {
+            return Err(format!(
+                "format type '{}' needs a number, got {}",
+                kind,
+                value.type_name()
+            ))
+        }
+    };
+    Ok(pad(body, &spec, is_number))
+}
+
+/// Formats a float for a format spec. The default spec gives the `str()` form, like Python's:
+/// integral values keep a trailing `.0`, and very large or small values use exponent notation,
+/// e.g. `1e+16` and `1e-05`
+pub fn format_float(f: f64, spec: &FormatSpec) -> String {
+    if !f.is_finite() {
+        return if f.is_nan() {
+            "nan"
+        } else if f > 0.0 {
+            "inf"
+        } else {
+            "-inf"
+        }
+        .to_string();
+    }
+    match (spec.kind, spec.precision) {
+        (Some(kind @ ('e' | 'E')), precision) => {
+            let text: String = exponent_notation(format!("{:.*e}", precision.unwrap_or(6), f));
+            if kind == 'E' {
+                text.to_uppercase()
+            } else {
+                text
+            }
+        }
+        (Some('%'), precision) => format!("{:.*}%", precision.unwrap_or(6), f * 100.0),
+        (Some('f'), None) => format!("{:.6}", f),
+        (_, Some(precision)) => format!("{:.*}", precision, f),
+        (_, None) if f != 0.0 && (f.abs() >= 1e16 || f.abs() < 1e-4) => {
+            exponent_notation(format!("{:e}", f))
+        }
+        (_, None) if f.fract() == 0.0 => format!("{:.1}", f),
+        (_, None) => f.to_string(),
+    }
+}
+
+/// Rewrites Rust's exponent notation the way Python writes it, with a signed exponent of at
+/// least two digits: `2.5e0` becomes `2.5e+00`
+fn exponent_notation(text: String) -> String {
+    let (mantissa, exponent) = text.split_once('e').unwrap();
+    let (sign, digits) = match exponent.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("+", exponent),
+    };
+    format!("{}e{}{:0>2}", mantissa, sign, digits)
+}
+
+/// Cuts text to the spec's precision, which for text is a maximum length
+fn truncate(text: String, spec: &FormatSpec) -> String {
+    match spec.precision {
+        Some(precision) => text.chars().take(precision).collect(),
+        None => text,
+    }
+}
+
+fn pad(body: String, spec: &FormatSpec, is_number: bool) -> String {
+    let (sign, digits): (&str, &str) = match body.strip_prefix('-') {
+        Some(rest) if is_number => ("-", rest),
+        _ if is_number && spec.sign => ("+", body.as_str()),
+        _ => ("", body.as_str()),
+    };
+    let length: usize = sign.len() + digits.chars().count();
+    if length >= spec.width {
+        return format!("{}{}", sign, digits);
+    }
+    let padding: usize = spec.width - length;
+    if spec.zero_pad && spec.align.is_none() && is_number {
+        return format!("{}{}{}", sign, "0".repeat(padding), digits);
+    }
+    let fill: String = spec.fill.unwrap_or(' ').to_string();
+    let default_align: char = if is_number { '>' } else { '<' };
+    match spec.align.unwrap_or(default_align) {
+        '<' => format!("{}{}{}", sign, digits, fill.repeat(padding)),
+        '^' => format!(
+            "{}{}{}{}",
+            fill.repeat(padding / 2),
+            sign,
+            digits,
+            fill.repeat(padding - padding / 2)
+        ),
+        _ => format!("{}{}{}", fill.repeat(padding), sign, digits),
+    }
+}
+
+/// Quotes a string and escapes the characters that string literals escape
+pub fn quote_string(s: &str) -> String {
+    let mut out: String = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn write_repr(out: &mut String, value: &Value, depth: usize) {
+    if depth > MAX_DEPTH {
+        out.push_str("...");
+        return;
+    }
+    match value {
+        Value::Integer(n) => {
+            let _ = write!(out, "{}", n.0);
+        }
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        Value::FloatNumber(f) => out.push_str(&format_float(f.0, &FormatSpec::default())),
+        Value::String(s) => out.push_str(&quote_string(&s.0)),
+        Value::Boolean(b) => {
+            let _ = write!(out, "{}", b.0);
+        }
+        Value::Array(array) => {
+            out.push('[');
+            write_items(out, array.iter(), depth, |out, item, depth| {
+                write_repr(out, item, depth)
+            });
+            out.push(']');
+        }
+        Value::Tuple(items) => {
+            out.push('(');
+            write_items(out, items.iter(), depth, |out, item, depth| {
+                write_repr(out, item, depth)
+            });
+            // A one-item tuple keeps its comma so it reads back as a tuple
+            if items.len() == 1 {
+                out.push(',');
+            }
+            out.push(')');
+        }
+        Value::Dict(dict) => {
+            out.push('{');
+            write_items(out, dict.iter(), depth, |out, (key, item), depth| {
+                write_repr(out, key, depth);
+                out.push_str(": ");
+                write_repr(out, item, depth);
+            });
+            out.push('}');
+        }
+        // {} is an empty dict, so an empty set shows as the call that makes one
+        Value::Set(set) if set.is_empty() => out.push_str("set()"),
+        Value::Set(set) => {
+            out.push('{');
+            write_items(out, set.iter(), depth, |out, item, depth| {
+                write_repr(out, item, depth)
+            });
+            out.push('}');
+        }
+        Value::Undefined => out.push_str("none"),
+        Value::Function { .. } => out.push_str("<function>"),
+        Value::BuiltinFunction { module, name } => {
+            let _ = write!(out, "<built-in function {}.{}>", module, name);
+        }
+        Value::Struct(struct_value) => {
+            out.push_str(&struct_value.name);
+            out.push_str(" { ");
+            write_items(
+                out,
+                struct_value.fields.iter(),
+                depth,
+                |out, (name, item), depth| {
+                    out.push_str(name);
+                    out.push_str(": ");
+                    write_repr(out, item, depth);
+                },
+            );
+            out.push_str(" }");
+        }
+        Value::StructType { name, .. } => {
+            let _ = write!(out, "<struct {}>", name);
+        }
+        Value::Module { name, .. } => {
+            let _ = write!(out, "<module {}>", name);
+        }
+    }
+}
+
+fn write_items<T>(
+    out: &mut String,
+    items: impl Iterator<Item = T>,
+    depth: usize,
+    mut write_item: impl FnMut(&mut String, T, usize),
+) {
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_item(out, item, depth + 1);
+    }
+}
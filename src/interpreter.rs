@@ -1,80 +1,105 @@
-use crate::eval::evaluate_expression::evaluate_expression;
+use crate::eval::compare::{compare, values_equal};
+use crate::eval::errors;
+use crate::eval::evaluate_expression::{attach_label, evaluate_expression};
+use crate::eval::functions::parse_parameters;
+use crate::eval::operators::apply_operator;
+use crate::eval::structs::{assign_path, get_field};
 use crate::parser::Rule;
 use crate::stdlib::StdLib;
-use crate::value::Value;
+use crate::value::{Integer, MethodTable, StringValue, Value};
+use miette::LabeledSpan;
 use pest::iterators::{Pair, Pairs};
+use pest::Span;
 use std::collections::HashMap;
 
-pub fn run_program(parsed: Pairs<Rule>) -> miette::Result<()> {
+/// Tells the caller whether execution continues normally or unwinds with a return value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Flow {
+    Next,
+    Return(Value),
+}
+
+pub fn run_program(parsed: Pairs<'static, Rule>) -> miette::Result<()> {
     let mut variables: HashMap<String, Value> = HashMap::new();
     let stdlib: StdLib = StdLib::new();
+    execute_program(parsed, &mut variables, &stdlib)
+}
+
+/// Runs top-level statements against the given variables, leaving them for the caller to inspect
+pub fn execute_program(
+    parsed: Pairs<'static, Rule>,
+    variables: &mut HashMap<String, Value>,
+    stdlib: &StdLib,
+) -> miette::Result<()> {
     for pair in parsed {
-        execute_statement(pair, &mut variables, &stdlib)?;
+        let span: Span = pair.as_span();
+        let flow: Flow = execute_statement(pair, variables, stdlib)
+            .map_err(|e| attach_label(e, span, "in this statement"))?;
+        if let Flow::Return(_) = flow {
+            break;
+        }
     }
     Ok(())
 }
 
 fn execute_statement(
-    pair: Pair<Rule>,
+    pair: Pair<'static, Rule>,
     variables: &mut HashMap<String, Value>,
     stdlib: &StdLib,
-) -> miette::Result<()> {
+) -> miette::Result<Flow> {
     match pair.as_rule() {
+        Rule::import_statement | Rule::from_import_statement => {
+            execute_import(pair, variables, stdlib)?;
+        }
         Rule::variable_declaration => {
             let mut inner: Pairs<Rule> = pair.into_inner();
-            let var_name: String = inner.next().unwrap().as_str().to_string();
+            let pattern: Pair<Rule> = inner.next().unwrap();
             let value: Value = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
-            variables.insert(var_name, value);
+            let mut bindings: Vec<(Pair<Rule>, Value)> = Vec::new();
+            destructure(pattern, value, &mut bindings)?;
+            for (name, value) in bindings {
+                variables.insert(name.as_str().to_string(), value);
+            }
         }
         Rule::assignment => {
             let mut inner: Pairs<Rule> = pair.into_inner();
-            let var_name: String = inner.next().unwrap().as_str().to_string();
+            let pattern: Pair<Rule> = inner.next().unwrap();
+            // The whole right-hand side is evaluated first, so `a, b = b, a` swaps
             let value: Value = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
-            if variables.contains_key(&var_name) {
-                variables.insert(var_name, value);
-            } else {
-                return Err(miette::miette!(
-                    "Assignment to undefined variable '{}'.",
-                    var_name
-                ));
+            let mut targets: Vec<(Pair<Rule>, Value)> = Vec::new();
+            destructure(pattern, value, &mut targets)?;
+            for (target, value) in targets {
+                let (var_name, path) = split_assign_target(target);
+                assign_path(variables, &var_name, &path, value)?;
             }
         }
         Rule::augmented_assignment => {
             let mut inner: Pairs<Rule> = pair.into_inner();
-            let var_name: String = inner.next().unwrap().as_str().to_string();
+            let target: Pair<Rule> = inner.next().unwrap();
+            let target_span: Span = target.as_span();
+            let (var_name, path) = split_assign_target(target);
             let op_pair: Pair<Rule> = inner.next().unwrap();
             let op: &str = op_pair.as_str();
             let value: Value = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
-            if let Some(current) = variables.get(&var_name).cloned() {
+            if let Some(mut current) = variables.get(&var_name).cloned() {
+                for field in &path {
+                    current = get_field(&current, field, target_span)?;
+                }
                 let op_str: &str = &op[..op.len() - 1];
                 let new_value: Value =
-                    crate::eval::operators::apply_operator(current, value, op_str)?;
-                variables.insert(var_name, new_value);
+                    crate::eval::operators::apply_operator(current, value, op_str)
+                        .map_err(|e| attach_label(e, op_pair.as_span(), "this operation"))?;
+                assign_path(variables, &var_name, &path, new_value)?;
             } else {
                 return Err(miette::miette!(
+                    code = "NameError",
                     "Assignment to undefined variable '{}'.",
                     var_name
                 ));
             }
         }
-        Rule::print_statement => {
-            let mut inner: Pairs<Rule> = pair.into_inner();
-            let value: Value = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
-            match value {
-                Value::String(string_value) => println!("{}", string_value),
-                Value::Integer(integer_value) => println!("{}", integer_value),
-                Value::FloatNumber(float_value) => println!("{}", float_value),
-                Value::Boolean(boolean_value) => println!("{}", boolean_value),
-                Value::Array(array) => println!("{:?}", array),
-                Value::Dict(dictionary) => println!("{:?}", dictionary),
-                Value::Function { .. } => println!("<function>"),
-                Value::Undefined => {
-                    return Err(miette::miette!("Attempted to print an undefined value."))
-                }
-            }
-        }
         Rule::if_statement => {
-            execute_if_statement(pair, variables, stdlib)?;
+            return execute_if_statement(pair, variables, stdlib);
         }
         Rule::while_statement => {
             let mut inner: Pairs<Rule> = pair.into_inner();
@@ -85,42 +110,77 @@ fn execute_statement(
                 if !is_truthy(cond_val.clone()) {
                     break;
                 }
-                let mut local_vars: HashMap<String, Value> = variables.clone();
-                execute_block(block.clone(), &mut local_vars, stdlib)?;
-                for (k, v) in local_vars.iter() {
-                    if variables.contains_key(k) {
-                        variables.insert(k.clone(), v.clone());
+                let flow: Flow = execute_block(block.clone(), variables, stdlib)?;
+                if let Flow::Return(_) = flow {
+                    return Ok(flow);
+                }
+            }
+        }
+        Rule::for_statement => {
+            let mut inner: Pairs<Rule> = pair.into_inner();
+            let pattern: Pair<Rule> = inner.next().unwrap();
+            let iterable: Pair<Rule> = inner.next().unwrap();
+            let iterable_span: Span = iterable.as_span();
+            let items: Vec<Value> = iterate(
+                evaluate_expression(iterable, variables, stdlib)?,
+                iterable_span,
+            )?;
+            let block: Pair<Rule> = inner.next().unwrap();
+            for item in items {
+                let mut bindings: Vec<(Pair<Rule>, Value)> = Vec::new();
+                destructure(pattern.clone(), item, &mut bindings)?;
+                let bindings: Vec<(String, Value)> = bindings
+                    .into_iter()
+                    .map(|(name, value)| (name.as_str().to_string(), value))
+                    .collect();
+                let flow: Flow = execute_with_bindings(block.clone(), bindings, variables, stdlib)?;
+                if let Flow::Return(_) = flow {
+                    return Ok(flow);
+                }
+            }
+        }
+        Rule::match_statement => {
+            let mut inner: Pairs<Rule> = pair.into_inner();
+            let subject: Value = evaluate_expression(inner.next().unwrap(), variables, stdlib)?;
+            for arm in inner {
+                let mut parts: Vec<Pair<Rule>> = arm.into_inner().collect();
+                let block: Pair<Rule> = parts.pop().unwrap();
+                for pattern in parts {
+                    let mut captures: Vec<(String, Value)> = Vec::new();
+                    if match_pattern(pattern, &subject, &mut captures, variables, stdlib)? {
+                        return execute_with_bindings(block, captures, variables, stdlib);
                     }
                 }
             }
         }
+        Rule::try_statement => {
+            return execute_try_statement(pair, variables, stdlib);
+        }
+        Rule::raise_statement => {
+            let span: Span = pair.as_span();
+            let value: Value =
+                evaluate_expression(pair.into_inner().nth(1).unwrap(), variables, stdlib)?;
+            return Err(errors::to_report(&value, span));
+        }
         Rule::function_definition => {
-            let mut inner = pair.into_inner();
-            let fn_name = inner.next().unwrap().as_str().to_string();
+            let mut inner: Pairs<Rule> = pair.into_inner();
+            let fn_name: String = inner.next().unwrap().as_str().to_string();
             let mut parameters = Vec::new();
-            let mut body = None;
-            if let Some(next) = inner.next() {
-                if next.as_rule() == Rule::IDENTIFIER {
-                    // At least one parameter
-                    parameters.push(next.as_str().to_string());
-                    for p in next.into_inner() {
-                        parameters.push(p.as_str().to_string());
-                    }
-                    // The next item must be the body
-                    if let Some(b) = inner.next() {
-                        body = Some(b.as_str().to_string());
-                    }
-                } else if next.as_rule() == Rule::block {
-                    // No parameters, next is body
-                    body = Some(next.as_str().to_string());
+            let mut body: Option<Pair<Rule>> = None;
+            for next in inner {
+                match next.as_rule() {
+                    Rule::param_list => parameters = parse_parameters(next, variables, stdlib)?,
+                    Rule::block => body = Some(next),
+                    _ => {}
                 }
             }
-            if let Some(body_str) = body {
+            if let Some(body) = body {
                 variables.insert(
-                    fn_name,
+                    fn_name.clone(),
                     Value::Function {
+                        name: fn_name,
                         parameters,
-                        body: body_str,
+                        body,
                         environment: variables.clone(),
                     },
                 );
@@ -128,45 +188,337 @@ fn execute_statement(
         }
         Rule::return_statement => {
             let value = evaluate_expression(pair.into_inner().next().unwrap(), variables, stdlib)?;
-            return Err(miette::miette!("__RETURN__{:?}", value));
+            return Ok(Flow::Return(value));
+        }
+        Rule::struct_definition => {
+            let mut inner: Pairs<Rule> = pair.into_inner();
+            let name: String = inner.next().unwrap().as_str().to_string();
+            let fields: Vec<String> = inner.map(|p: Pair<Rule>| p.as_str().to_string()).collect();
+            variables.insert(
+                name.clone(),
+                Value::StructType {
+                    name,
+                    fields,
+                    methods: MethodTable::default(),
+                },
+            );
+        }
+        Rule::impl_block => {
+            let mut inner: Pairs<Rule> = pair.into_inner();
+            let name_pair: Pair<Rule> = inner.next().unwrap();
+            let name: String = name_pair.as_str().to_string();
+            if !matches!(variables.get(&name), Some(Value::StructType { .. })) {
+                let span: Span = name_pair.as_span();
+                return Err(miette::miette!(
+                    code = "NameError",
+                    labels = vec![LabeledSpan::at(span.start()..span.end(), "not a struct")],
+                    "Cannot implement methods for undefined struct '{}'.",
+                    name
+                ));
+            }
+            // Methods are defined in a scratch scope and then moved onto the struct type
+            let mut method_scope: HashMap<String, Value> = variables.clone();
+            let mut defined: HashMap<String, Value> = HashMap::new();
+            for definition in inner {
+                let method_name: String = definition
+                    .clone()
+                    .into_inner()
+                    .next()
+                    .unwrap()
+                    .as_str()
+                    .to_string();
+                execute_statement(definition, &mut method_scope, stdlib)?;
+                if let Some(method) = method_scope.remove(&method_name) {
+                    defined.insert(method_name, method);
+                }
+            }
+            if let Some(Value::StructType { methods, .. }) = variables.get(&name) {
+                methods.extend(defined);
+            }
+        }
+        Rule::expression_statement => {
+            evaluate_expression(pair.into_inner().next().unwrap(), variables, stdlib)?;
         }
         _ => {}
     }
+    Ok(Flow::Next)
+}
+
+/// Runs `import module` or `from module import name, ...`: `import` binds the module's namespace
+/// to its name, and `from` binds the names it lists
+fn execute_import(
+    pair: Pair<'static, Rule>,
+    variables: &mut HashMap<String, Value>,
+    stdlib: &StdLib,
+) -> miette::Result<()> {
+    let span: Span = pair.as_span();
+    let is_from: bool = pair.as_rule() == Rule::from_import_statement;
+    let mut inner: Pairs<Rule> = pair.into_inner();
+    inner.next(); // `import` or `from`
+    let target: Pair<Rule> = inner.next().unwrap();
+    // `import math` is a standard library module, `import utils` finds utils.tung, and
+    // `import "lib/utils.tung"` names the file itself
+    let module: Value = match target.as_rule() {
+        Rule::string => {
+            let path: String = evaluate_expression(target, variables, stdlib)?.to_string();
+            stdlib.modules().import(&path, span, stdlib)?
+        }
+        _ => match stdlib.std_module_value(target.as_str()) {
+            Some(module) => module,
+            None => {
+                let path: String = format!("{}.tung", target.as_str());
+                stdlib.modules().import(&path, span, stdlib)?
+            }
+        },
+    };
+    let Value::Module { name, members } = &module else {
+        unreachable!("imports produce modules");
+    };
+    if !is_from {
+        variables.insert(name.clone(), module.clone());
+        return Ok(());
+    }
+    inner.next(); // `import`
+    for imported in inner {
+        let imported_span: Span = imported.as_span();
+        let Some(value) = members.get(imported.as_str()) else {
+            return Err(miette::miette!(
+                code = "ImportError",
+                labels = vec![LabeledSpan::at(
+                    imported_span.start()..imported_span.end(),
+                    "not in the module"
+                )],
+                "Error: Module '{}' has no name '{}'.",
+                name,
+                imported.as_str()
+            ));
+        };
+        variables.insert(imported.as_str().to_string(), value.clone());
+    }
+    Ok(())
+}
+
+/// Splits an assignment target like `p.pos.x` into the variable name and its field path
+fn split_assign_target(target: Pair<Rule>) -> (String, Vec<String>) {
+    let mut parts: Pairs<Rule> = target.into_inner();
+    let var_name: String = parts.next().unwrap().as_str().to_string();
+    let path: Vec<String> = parts.map(|p: Pair<Rule>| p.as_str().to_string()).collect();
+    (var_name, path)
+}
+
+/// Pairs each leaf of a destructuring pattern with its part of `value`.
+/// Leaves are names for `var` and `for`, and assignment targets for `=`.
+fn destructure(
+    pattern: Pair<'static, Rule>,
+    value: Value,
+    leaves: &mut Vec<(Pair<'static, Rule>, Value)>,
+) -> miette::Result<()> {
+    if !matches!(
+        pattern.as_rule(),
+        Rule::bindings | Rule::tuple_binding | Rule::targets | Rule::tuple_target
+    ) {
+        leaves.push((pattern, value));
+        return Ok(());
+    }
+    let span: Span = pattern.as_span();
+    let label = |text: &str| vec![LabeledSpan::at(span.start()..span.end(), text.to_string())];
+    let parts: Vec<Pair<Rule>> = pattern.into_inner().collect();
+    let items: Vec<Value> = match value {
+        Value::Tuple(items) | Value::Array(items) => items,
+        other => {
+            return Err(miette::miette!(
+                code = "TypeError",
+                labels = label("cannot unpack"),
+                "Error: Cannot unpack {} into {} names.",
+                other.type_name(),
+                parts.len()
+            ))
+        }
+    };
+    if items.len() != parts.len() {
+        return Err(miette::miette!(
+            code = "ValueError",
+            labels = label("in this pattern"),
+            "Error: Expected {} values to unpack, got {}.",
+            parts.len(),
+            items.len()
+        ));
+    }
+    for (part, item) in parts.into_iter().zip(items) {
+        destructure(part, item, leaves)?;
+    }
     Ok(())
 }
 
-fn is_truthy(value: Value) -> bool {
+/// Runs a loop body or match arm with names bound for it alone: they never overwrite outer
+/// variables, while other changes the block makes are kept
+fn execute_with_bindings(
+    block: Pair<'static, Rule>,
+    bindings: Vec<(String, Value)>,
+    variables: &mut HashMap<String, Value>,
+    stdlib: &StdLib,
+) -> miette::Result<Flow> {
+    let mut local_vars: HashMap<String, Value> = variables.clone();
+    let mut bound_names: Vec<String> = Vec::new();
+    for (name, value) in bindings {
+        local_vars.insert(name.clone(), value);
+        bound_names.push(name);
+    }
+    let flow: miette::Result<Flow> = execute_block(block, &mut local_vars, stdlib);
+    for (k, v) in local_vars.iter() {
+        if variables.contains_key(k) && !bound_names.contains(k) {
+            variables.insert(k.clone(), v.clone());
+        }
+    }
+    flow
+}
+
+/// Whether a value fits a `match` pattern, collecting the names the pattern captures
+fn match_pattern(
+    pattern: Pair<'static, Rule>,
+    value: &Value,
+    captures: &mut Vec<(String, Value)>,
+    variables: &mut HashMap<String, Value>,
+    stdlib: &StdLib,
+) -> miette::Result<bool> {
+    match pattern.as_rule() {
+        Rule::wildcard_pattern => Ok(true),
+        Rule::capture_pattern => {
+            captures.push((pattern.as_str().to_string(), value.clone()));
+            Ok(true)
+        }
+        Rule::literal_pattern => {
+            let literal: Value =
+                pattern_literal(pattern.into_inner().next().unwrap(), variables, stdlib)?;
+            Ok(values_equal(value, &literal))
+        }
+        Rule::range_pattern => {
+            let mut inner: Pairs<Rule> = pattern.into_inner();
+            let start: Value = pattern_literal(inner.next().unwrap(), variables, stdlib)?;
+            let end_op: &str = if inner.next().unwrap().as_str() == "..=" {
+                "<="
+            } else {
+                "<"
+            };
+            let end: Value = pattern_literal(inner.next().unwrap(), variables, stdlib)?;
+            // A value that cannot be compared with the bounds, like a string, is simply not in range
+            Ok(compare(value, &start, ">=").unwrap_or(false)
+                && compare(value, &end, end_op).unwrap_or(false))
+        }
+        Rule::array_pattern => {
+            let span: Span = pattern.as_span();
+            let items: &[Value] = match value {
+                Value::Array(items) | Value::Tuple(items) => items,
+                _ => return Ok(false),
+            };
+            let parts: Vec<Pair<Rule>> = pattern.into_inner().collect();
+            let rests: Vec<usize> = (0..parts.len())
+                .filter(|&i| parts[i].as_rule() == Rule::rest_pattern)
+                .collect();
+            let (before, after): (usize, usize) = match rests.as_slice() {
+                [] if items.len() == parts.len() => (parts.len(), 0),
+                [] => return Ok(false),
+                [rest] if items.len() + 1 >= parts.len() => (*rest, parts.len() - rest - 1),
+                [_] => return Ok(false),
+                _ => {
+                    return Err(miette::miette!(
+                        code = "SyntaxError",
+                        labels = vec![LabeledSpan::at(span.start()..span.end(), "this pattern")],
+                        "Error: An array pattern can only have one *rest."
+                    ))
+                }
+            };
+            let rest_items: &[Value] = &items[before..items.len() - after];
+            let mut remaining = items[..before].iter().chain(&items[items.len() - after..]);
+            for part in parts {
+                if part.as_rule() == Rule::rest_pattern {
+                    let name: &str = part.into_inner().next().unwrap().as_str();
+                    captures.push((name.to_string(), Value::Array(rest_items.to_vec())));
+                } else if !match_pattern(
+                    part,
+                    remaining.next().unwrap(),
+                    captures,
+                    variables,
+                    stdlib,
+                )? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        _ => Err(miette::miette!("Error: Invalid pattern.")),
+    }
+}
+
+/// The value of a literal in a pattern; numbers may be negative
+fn pattern_literal(
+    literal: Pair<'static, Rule>,
+    variables: &mut HashMap<String, Value>,
+    stdlib: &StdLib,
+) -> miette::Result<Value> {
+    if literal.as_rule() != Rule::signed_number {
+        return evaluate_expression(literal, variables, stdlib);
+    }
+    let negative: bool = literal.as_str().starts_with('-');
+    let number: Value =
+        evaluate_expression(literal.into_inner().next().unwrap(), variables, stdlib)?;
+    if negative {
+        apply_operator(Value::Integer(Integer(0)), number, "-")
+    } else {
+        Ok(number)
+    }
+}
+
+/// The items a `for` loop visits: array, tuple and set items, string characters or dict keys
+fn iterate(value: Value, span: Span) -> miette::Result<Vec<Value>> {
+    match value {
+        Value::Array(items) | Value::Tuple(items) => Ok(items),
+        Value::String(s) => Ok(s
+            .0
+            .chars()
+            .map(|c: char| Value::String(StringValue(c.to_string())))
+            .collect()),
+        Value::Dict(dict) => Ok(dict.keys().cloned().collect()),
+        Value::Set(set) => Ok(set.iter().cloned().collect()),
+        other => Err(miette::miette!(
+            code = "TypeError",
+            labels = vec![LabeledSpan::at(span.start()..span.end(), "not iterable")],
+            "Error: Cannot iterate over {}.",
+            other.type_name()
+        )),
+    }
+}
+
+pub(crate) fn is_truthy(value: Value) -> bool {
     match value {
         Value::Integer(integer_value) => integer_value.0 != 0,
+        // Big integers are never zero, since zero always fits in an i64
+        #[cfg(feature = "bigint")]
+        Value::BigInteger(_) => true,
         Value::FloatNumber(float_value) => float_value.0 != 0.0,
         Value::String(string_value) => !string_value.0.is_empty(),
         Value::Boolean(boolean_value) => boolean_value.0,
-        Value::Array(ref array) => !array.is_empty(),
+        Value::Array(ref array) | Value::Tuple(ref array) => !array.is_empty(),
         Value::Dict(ref dictionary) => !dictionary.is_empty(),
+        Value::Set(ref set) => !set.is_empty(),
         Value::Undefined => false,
-        Value::Function { .. } => false,
+        Value::Function { .. } | Value::BuiltinFunction { .. } => false,
+        Value::Struct(_) | Value::StructType { .. } | Value::Module { .. } => true,
     }
 }
 
 fn execute_if_statement(
-    pair: Pair<Rule>,
+    pair: Pair<'static, Rule>,
     variables: &mut HashMap<String, Value>,
     stdlib: &StdLib,
-) -> miette::Result<()> {
+) -> miette::Result<Flow> {
     let mut inner: Pairs<Rule> = pair.into_inner();
     let condition: Pair<Rule> = inner.next().unwrap();
     let block: Pair<Rule> = inner.next().unwrap();
     let cond_val = evaluate_expression(condition, variables, stdlib)?;
     let condition_met: bool = is_truthy(cond_val);
     if condition_met {
-        let mut local_vars: HashMap<String, Value> = variables.clone();
-        execute_block(block, &mut local_vars, stdlib)?;
-        for (k, v) in local_vars.iter() {
-            if variables.contains_key(k) {
-                variables.insert(k.clone(), v.clone());
-            }
-        }
-        return Ok(());
+        return execute_block(block, variables, stdlib);
     } else {
         for elif_or_else in inner {
             match elif_or_else.as_rule() {
@@ -177,47 +529,96 @@ fn execute_if_statement(
                     let elif_val = evaluate_expression(elif_condition, variables, stdlib)?;
                     let elif_met: bool = is_truthy(elif_val);
                     if elif_met {
-                        let mut local_vars: HashMap<String, Value> = variables.clone();
-                        execute_block(elif_block, &mut local_vars, stdlib)?;
-                        for (k, v) in local_vars.iter() {
-                            if variables.contains_key(k) {
-                                variables.insert(k.clone(), v.clone());
-                            }
-                        }
-                        return Ok(());
+                        return execute_block(elif_block, variables, stdlib);
                     }
                 }
                 Rule::else_block => {
                     let else_block: Pair<Rule> = elif_or_else.into_inner().next().unwrap();
-                    let mut local_vars: HashMap<String, Value> = variables.clone();
-                    execute_block(else_block, &mut local_vars, stdlib)?;
-                    for (k, v) in local_vars.iter() {
-                        if variables.contains_key(k) {
-                            variables.insert(k.clone(), v.clone());
-                        }
-                    }
-                    return Ok(());
+                    return execute_block(else_block, variables, stdlib);
                 }
                 _ => {}
             }
         }
     }
-    Ok(())
+    Ok(Flow::Next)
+}
+
+/// Runs a try statement: the first catch clause that handles the try block's error runs with the
+/// error bound to its name, then the finally block runs whatever happened. An error or return in
+/// the finally block replaces the earlier outcome.
+fn execute_try_statement(
+    pair: Pair<'static, Rule>,
+    variables: &mut HashMap<String, Value>,
+    stdlib: &StdLib,
+) -> miette::Result<Flow> {
+    let source: &str = pair.as_span().get_input();
+    let mut inner: Pairs<Rule> = pair.into_inner();
+    let mut result: miette::Result<Flow> = execute_block(inner.next().unwrap(), variables, stdlib);
+    let mut handled: bool = false;
+    for clause in inner {
+        if clause.as_rule() == Rule::finally_clause {
+            let finally: Flow =
+                execute_block(clause.into_inner().next().unwrap(), variables, stdlib)?;
+            if let Flow::Return(_) = finally {
+                return Ok(finally);
+            }
+            continue;
+        }
+        let Err(error) = &result else {
+            continue;
+        };
+        if handled {
+            continue;
+        }
+        let mut parts: Vec<Pair<Rule>> = clause.into_inner().collect();
+        let block: Pair<Rule> = parts.pop().unwrap();
+        let name: Option<Pair<Rule>> = parts
+            .last()
+            .filter(|part: &&Pair<Rule>| part.as_rule() == Rule::IDENTIFIER)
+            .cloned();
+        let kinds: Vec<&str> = parts
+            .iter()
+            .filter(|part: &&Pair<Rule>| part.as_rule() == Rule::error_kind)
+            .map(|part: &Pair<Rule>| part.as_str())
+            .collect();
+        if !errors::catches(&kinds, &errors::error_kind(error)) {
+            continue;
+        }
+        let bindings: Vec<(String, Value)> = name
+            .map(|name: Pair<Rule>| {
+                (
+                    name.as_str().to_string(),
+                    errors::from_report(error, source),
+                )
+            })
+            .into_iter()
+            .collect();
+        handled = true;
+        result = execute_with_bindings(block, bindings, variables, stdlib);
+    }
+    result
 }
 
 pub fn execute_block(
-    block: Pair<Rule>,
+    block: Pair<'static, Rule>,
     variables: &mut HashMap<String, Value>,
     stdlib: &StdLib,
-) -> miette::Result<()> {
+) -> miette::Result<Flow> {
     let mut local_vars: HashMap<String, Value> = variables.clone();
+    let mut flow: miette::Result<Flow> = Ok(Flow::Next);
     for statement in block.into_inner() {
-        execute_statement(statement, &mut local_vars, stdlib)?;
+        let span: Span = statement.as_span();
+        flow = execute_statement(statement, &mut local_vars, stdlib)
+            .map_err(|e| attach_label(e, span, "in this statement"));
+        if !matches!(flow, Ok(Flow::Next)) {
+            break;
+        }
     }
+    // Changes made before an error are kept too, so a `catch` sees them
     for (k, v) in local_vars.iter() {
         if variables.contains_key(k) {
             variables.insert(k.clone(), v.clone());
         }
     }
-    Ok(())
+    flow
 }
@@ -1,8 +1,16 @@
+#[cfg(feature = "bigint")]
+pub mod bigint;
 pub mod eval;
+pub mod format;
 pub mod interpreter;
+pub mod modules;
+pub mod output;
 pub mod parser;
+pub mod preprocess;
 pub mod stdlib;
+pub mod testing;
 pub mod value;
+pub mod warnings;
 pub use crate::interpreter::execute_block;
 pub use crate::parser::TungParser;
 pub use pest::Parser;
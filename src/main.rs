@@ -1,56 +1,161 @@
-mod eval;
-mod interpreter;
-mod parser;
-mod preprocess;
-mod stdlib;
-mod value;
-use crate::interpreter::run_program;
-use crate::parser::{Rule, TungParser};
-use crate::preprocess::preprocess_code;
-use ::std::ffi;
 use ::std::fs;
 use ::std::path;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use pest::iterators::Pairs;
 use pest::Parser as PestParserTrait;
+use std::collections::HashMap;
+use tung_lang::eval::string_literal::check_escapes;
+use tung_lang::interpreter::execute_program;
+use tung_lang::parser::{syntax_error, Rule, TungParser};
+use tung_lang::preprocess::preprocess_code;
+use tung_lang::testing::{discover, is_tung_file, run_tests, test_functions, TestResult};
+use tung_lang::warnings::check_program;
+use tung_lang::StdLib;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
     /// Path to the TungLang source file
-    #[arg(short, long)]
-    pub file: String,
+    #[arg(short, long, required = true)]
+    pub file: Option<String>,
+
+    /// Seed for the `random` module, so every run draws the same numbers
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the `fn test_*` functions in .tung files
+    Test {
+        /// Files or directories to search for tests; the current directory by default
+        paths: Vec<path::PathBuf>,
+    },
 }
 
 fn main() -> miette::Result<()> {
     let args: Args = Args::parse();
+    match (args.command, args.file) {
+        (Some(Command::Test { paths }), _) => test(paths, args.seed),
+        (None, Some(file)) => run(&file, args.seed),
+        (None, None) => unreachable!("clap requires --file without a subcommand"),
+    }
+}
+
+fn run(file: &str, seed: Option<u64>) -> miette::Result<()> {
+    let (program, parsed) = load(file)?;
+    print_warnings(&parsed, file, program);
 
-    let path: &path::Path = path::Path::new(&args.file);
-    if path
-        .extension()
-        .and_then(|s: &ffi::OsStr| s.to_str())
-        .map(|s: &str| s.eq_ignore_ascii_case("tung"))
-        != Some(true)
-    {
+    let mut stdlib: StdLib = StdLib::new();
+    if let Some(seed) = seed {
+        stdlib = stdlib.with_seed(seed);
+    }
+    stdlib
+        .modules()
+        .register_entry(program, path::Path::new(file));
+    execute_program(parsed, &mut HashMap::new(), &stdlib).map_err(|e| with_source(e, file, program))
+}
+
+/// Prints the warnings for a program to stderr; the program still runs
+fn print_warnings(parsed: &Pairs<'static, Rule>, file: &str, program: &'static str) {
+    for warning in check_program(parsed.clone()) {
+        let warning = warning.with_source_code(miette::NamedSource::new(file, program));
+        eprintln!("{:?}", warning);
+    }
+}
+
+/// Attaches the file an error happened in, unless it already names an imported module's file
+fn with_source(error: miette::Report, file: &str, program: &'static str) -> miette::Report {
+    if error.source_code().is_some() {
+        return error;
+    }
+    error.with_source_code(miette::NamedSource::new(file, program))
+}
+
+/// Runs the tests in every .tung file under `paths`, failing if any test fails
+fn test(mut paths: Vec<path::PathBuf>, seed: Option<u64>) -> miette::Result<()> {
+    if paths.is_empty() {
+        paths.push(path::PathBuf::from("."));
+    }
+    let files: Vec<path::PathBuf> =
+        discover(&paths).map_err(|e| miette::miette!("Error finding tests: {}", e))?;
+    let (mut passed, mut failed): (usize, usize) = (0, 0);
+    for file in files {
+        let name: String = file.display().to_string();
+        let (program, parsed) = match load(&name) {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                println!("{}\n  FAIL (could not load)\n{:?}", name, error);
+                failed += 1;
+                continue;
+            }
+        };
+        if test_functions(parsed.clone()).is_empty() {
+            continue;
+        }
+        println!("{}", name);
+        print_warnings(&parsed, &name, program);
+        for result in run_tests(parsed, Some(&file), seed) {
+            let TestResult {
+                name: test_name,
+                error,
+                output,
+            } = result;
+            match error {
+                None => {
+                    println!("  PASS {}", test_name);
+                    passed += 1;
+                }
+                Some(error) => {
+                    println!("  FAIL {}", test_name);
+                    let error = with_source(error, &name, program);
+                    println!("{:?}", error);
+                    if !output.is_empty() {
+                        println!("  output:\n{}", output.trim_end());
+                    }
+                    failed += 1;
+                }
+            }
+        }
+    }
+    println!("\n{} passed, {} failed", passed, failed);
+    if failed > 0 {
+        return Err(miette::miette!(
+            "Error: {} of {} tests failed.",
+            failed,
+            passed + failed
+        ));
+    }
+    Ok(())
+}
+
+/// Reads, preprocesses and parses a .tung file
+fn load(file: &str) -> miette::Result<(&'static str, Pairs<'static, Rule>)> {
+    if !is_tung_file(path::Path::new(file)) {
         return Err(miette::miette!("Error: Only .tung files are allowed."));
     }
 
-    let program: String = match fs::read_to_string(&args.file) {
+    let program: String = match fs::read_to_string(file) {
         Ok(content) => content,
         Err(e) => {
-            return Err(miette::miette!("Error reading file {}: {}", args.file, e));
+            return Err(miette::miette!("Error reading file {}: {}", file, e));
         }
     };
 
-    let program = preprocess_code(&program);
+    // Function values keep their parsed bodies, so the source lives for the whole run
+    let program: &'static str = Box::leak(preprocess_code(&program).into_boxed_str());
 
-    let parsed: pest::iterators::Pairs<Rule> = match TungParser::parse(Rule::program, &program) {
+    let parsed = match TungParser::parse(Rule::program, program) {
         Ok(mut pairs) => pairs.next().unwrap().into_inner(),
         Err(e) => {
-            return Err(miette::miette!("Error parsing program: {}", e));
+            let diagnostic = miette::diagnostic!("Error parsing program: {}", e);
+            return Err(syntax_error(diagnostic, program, &e));
         }
     };
-
-    run_program(parsed)?;
-
-    Ok(())
+    check_escapes(parsed.clone()).map_err(|e| with_source(e, file, program))?;
+    Ok((program, parsed))
 }
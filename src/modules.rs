@@ -0,0 +1,206 @@
+// Imports: finding, loading and caching the .tung files a program imports
+use crate::eval::string_literal::check_escapes;
+use crate::interpreter::execute_program;
+use crate::parser::{syntax_error, Rule, TungParser};
+use crate::preprocess::preprocess_code;
+use crate::stdlib::StdLib;
+use crate::value::{Dict, Value};
+use crate::warnings::check_program;
+use miette::{LabeledSpan, NamedSource};
+use pest::Parser;
+use pest::Span;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The environment variable listing extra directories to search for modules, like `PATH`
+pub const SEARCH_PATH_VARIABLE: &str = "TUNG_PATH";
+
+/// The source files of a running program and the modules it has imported
+#[derive(Debug, Default)]
+pub struct Modules {
+    /// Directories searched after the importing file's own directory
+    search_path: Vec<PathBuf>,
+    /// The file each loaded source text came from, keyed by the text's address
+    files: RefCell<HashMap<usize, PathBuf>>,
+    /// Modules that have finished running, by canonical path, so each one runs only once
+    loaded: RefCell<HashMap<PathBuf, Value>>,
+    /// Modules still running, outermost first, to catch circular imports
+    loading: RefCell<Vec<PathBuf>>,
+}
+
+impl Modules {
+    pub fn new(search_path: Vec<PathBuf>) -> Self {
+        Self {
+            search_path,
+            ..Self::default()
+        }
+    }
+
+    /// Modules searched for in the directories listed in `TUNG_PATH`
+    pub fn from_env() -> Self {
+        let search_path: Vec<PathBuf> = env::var_os(SEARCH_PATH_VARIABLE)
+            .map(|paths| env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        Self::new(search_path)
+    }
+
+    /// Records which file a program's source came from, so its imports are found next to it
+    /// and its errors show its name
+    pub fn register(&self, source: &'static str, file: &Path) {
+        self.files
+            .borrow_mut()
+            .insert(source.as_ptr() as usize, file.to_path_buf());
+    }
+
+    /// Registers the file a program starts from, like `register`, and counts it as loading
+    /// for the whole run, so a module that imports it back is a circular import
+    pub fn register_entry(&self, source: &'static str, file: &Path) {
+        self.register(source, file);
+        self.loading.borrow_mut().push(canonical(file));
+    }
+
+    /// The file a registered source text came from
+    pub fn file_of(&self, source: &str) -> Option<PathBuf> {
+        self.files
+            .borrow()
+            .get(&(source.as_ptr() as usize))
+            .cloned()
+    }
+
+    /// Gives an error raised in `source` that source's text and file name, when it leaves that
+    /// file for code in `caller_source`; its labels point into `source`, not the caller's file
+    pub fn attach_source(
+        &self,
+        error: miette::Report,
+        source: &'static str,
+        caller_source: &str,
+    ) -> miette::Report {
+        if source.as_ptr() == caller_source.as_ptr() || error.source_code().is_some() {
+            return error;
+        }
+        match self.file_of(source) {
+            Some(file) => {
+                error.with_source_code(NamedSource::new(file.display().to_string(), source))
+            }
+            None => error,
+        }
+    }
+
+    /// Runs the module at `target`, a path relative to the importing file or a search path
+    /// directory, and returns its namespace. A module runs only the first time it is imported.
+    pub fn import(&self, target: &str, span: Span, stdlib: &StdLib) -> miette::Result<Value> {
+        let label = |text: &str| vec![LabeledSpan::at(span.start()..span.end(), text.to_string())];
+        let directories: Vec<PathBuf> = self.directories(span.get_input());
+        let Some(file) = directories
+            .iter()
+            .map(|directory: &PathBuf| directory.join(target))
+            .find(|file: &PathBuf| file.is_file())
+        else {
+            let searched: Vec<String> = directories
+                .iter()
+                .map(|directory: &PathBuf| directory.display().to_string())
+                .collect();
+            return Err(miette::miette!(
+                code = "ImportError",
+                labels = label("imported here"),
+                "Error: No module '{}' in {}.",
+                target,
+                searched.join(", ")
+            ));
+        };
+        let key: PathBuf = canonical(&file);
+        if let Some(module) = self.loaded.borrow().get(&key) {
+            return Ok(module.clone());
+        }
+        if let Some(start) = self.loading.borrow().iter().position(|p| *p == key) {
+            let cycle: Vec<String> = self.loading.borrow()[start..]
+                .iter()
+                .chain(Some(&key))
+                .map(|p: &PathBuf| display_name(p))
+                .collect();
+            return Err(miette::miette!(
+                code = "ImportError",
+                labels = label("imported here"),
+                "Error: Circular import: {}.",
+                cycle.join(" -> ")
+            ));
+        }
+
+        let text: String = fs::read_to_string(&file).map_err(|e| {
+            miette::miette!(
+                code = "IOError",
+                labels = label("imported here"),
+                "Error reading module {}: {}",
+                file.display(),
+                e
+            )
+        })?;
+        // Function values keep their parsed bodies, so the source lives for the whole run
+        let source: &'static str = Box::leak(preprocess_code(&text).into_boxed_str());
+        self.register(source, &file);
+        let parsed = TungParser::parse(Rule::program, source)
+            .map_err(|e| {
+                let diagnostic = miette::diagnostic!(
+                    code = "SyntaxError",
+                    labels = label("imported here"),
+                    "Error parsing module {}: {}",
+                    file.display(),
+                    e
+                );
+                syntax_error(diagnostic, source, &e)
+            })?
+            .next()
+            .unwrap()
+            .into_inner();
+        check_escapes(parsed.clone())
+            .map_err(|e| self.attach_source(e, source, span.get_input()))?;
+        for warning in check_program(parsed.clone()) {
+            let warning =
+                warning.with_source_code(NamedSource::new(file.display().to_string(), source));
+            stdlib.write_error_output(&format!("{:?}\n", warning))?;
+        }
+
+        self.loading.borrow_mut().push(key.clone());
+        let mut members: Dict = HashMap::new();
+        let result: miette::Result<()> = execute_program(parsed, &mut members, stdlib);
+        self.loading.borrow_mut().pop();
+        result.map_err(|e| self.attach_source(e, source, span.get_input()))?;
+
+        let module: Value = Value::Module {
+            name: file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            members,
+        };
+        self.loaded.borrow_mut().insert(key, module.clone());
+        Ok(module)
+    }
+
+    /// Where imports from `source` are looked for: its file's directory (the current directory
+    /// if it has no file), then the search path
+    fn directories(&self, source: &str) -> Vec<PathBuf> {
+        let own: PathBuf = self
+            .file_of(source)
+            .and_then(|file: PathBuf| file.parent().map(Path::to_path_buf))
+            .filter(|directory: &PathBuf| !directory.as_os_str().is_empty())
+            .unwrap_or_else(|| PathBuf::from("."));
+        std::iter::once(own)
+            .chain(self.search_path.iter().cloned())
+            .collect()
+    }
+}
+
+/// The one path a file is known by, however it was reached
+fn canonical(file: &Path) -> PathBuf {
+    fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf())
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
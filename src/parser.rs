@@ -1,5 +1,34 @@
+use pest::error::{Error, InputLocation};
+use pest::Parser;
 use pest_derive::Parser;
 
 #[derive(Parser)]
 #[grammar = "src/tung.pest"]
 pub struct TungParser;
+
+/// A parse error for `program`, with a hint when the line it failed on has a `//` meant as a
+/// comment: `//` is floor division, so old programs need `#`. It is a comment when changing
+/// that `//` to `#` makes the program parse.
+pub fn syntax_error(
+    diagnostic: miette::MietteDiagnostic,
+    program: &str,
+    error: &Error<Rule>,
+) -> miette::Report {
+    let offset: usize = match error.location {
+        InputLocation::Pos(offset) | InputLocation::Span((offset, _)) => offset,
+    };
+    let line_start: usize = program[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end: usize = program[offset..]
+        .find('\n')
+        .map_or(program.len(), |i| offset + i);
+    let is_comment = |slashes: usize| {
+        let fixed: String = format!("{}#{}", &program[..slashes], &program[slashes + 1..]);
+        TungParser::parse(Rule::program, &fixed).is_ok()
+    };
+    match program[line_start..line_end].find("//") {
+        Some(column) if is_comment(line_start + column) => miette::Report::from(
+            diagnostic.with_help("line comments start with `#`; `//` is floor division"),
+        ),
+        _ => miette::Report::from(diagnostic),
+    }
+}
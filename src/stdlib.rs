@@ -1,82 +1,413 @@
 // TungLang standard library setup and function dispatch
+use crate::eval::errors;
+use crate::eval::methods::{BuiltinMethod, Methods};
 use crate::eval::std::std_abs::std_abs;
+use crate::eval::std::std_assert::std_assert;
 use crate::eval::std::std_cast;
 use crate::eval::std::std_input::tunglang_input;
 use crate::eval::std::std_len::std_len;
-use crate::eval::std::std_list;
 use crate::eval::std::std_math;
-use crate::eval::std::std_print::std_print;
+use crate::eval::std::std_os;
+use crate::eval::std::std_print::{std_eprint, std_print};
+use crate::eval::std::std_random::{self, Rng};
 use crate::eval::std::std_range;
-use crate::value::{BuiltinFn, Integer, StringValue, Value};
+use crate::eval::std::std_set;
+use crate::modules::Modules;
+use crate::value::{BuiltinFn, Dict, FloatNumber, Integer, StringValue, Value};
+use std::cell::{RefCell, RefMut};
 use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
 
-pub struct StdLib {
+/// A built-in that uses the standard library's state, such as its output streams, or that
+/// reports errors as diagnostics; it may take keyword arguments
+pub type StatefulFn =
+    fn(stdlib: &StdLib, args: &[Value], kwargs: &[(String, Value)]) -> miette::Result<Value>;
+
+/// A prelude function that runs the built-in method of the same name on its first argument
+macro_rules! method_function {
+    ($name:literal) => {
+        |stdlib: &StdLib, args: &[Value], kwargs: &[(String, Value)]| {
+            stdlib.methods.call_function($name, args, kwargs)
+        }
+    };
+}
+
+/// A group of built-in functions: the prelude every program sees, or a module like `math`
+#[derive(Default)]
+pub struct StdModule {
     functions: HashMap<&'static str, BuiltinFn>,
+    stateful_functions: HashMap<&'static str, StatefulFn>,
+    /// Named values such as `math.pi`
+    constants: HashMap<&'static str, Value>,
+}
+
+impl StdModule {
+    pub fn get(&self, name: &str) -> Option<&BuiltinFn> {
+        self.functions.get(name)
+    }
+
+    /// Looks up a built-in that needs the standard library itself, such as `print`
+    pub fn get_stateful(&self, name: &str) -> Option<&StatefulFn> {
+        self.stateful_functions.get(name)
+    }
+
+    /// The names of the functions in this group, sorted
+    pub fn function_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self
+            .functions
+            .keys()
+            .chain(self.stateful_functions.keys())
+            .copied()
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// A module holding the named functions of this one
+    fn select(&self, names: &[&'static str]) -> StdModule {
+        let mut module: StdModule = StdModule::default();
+        for name in names {
+            if let Some(function) = self.functions.get(name) {
+                module.functions.insert(name, *function);
+            } else if let Some(function) = self.stateful_functions.get(name) {
+                module.stateful_functions.insert(name, *function);
+            }
+        }
+        module
+    }
+}
+
+pub struct StdLib {
+    prelude: StdModule,
+    std_modules: HashMap<&'static str, StdModule>,
+    methods: Methods,
+    modules: Modules,
+    /// The generator behind the `random` module
+    rng: RefCell<Rng>,
+    input: RefCell<Box<dyn BufRead>>,
+    output: RefCell<Box<dyn Write>>,
+    error_output: RefCell<Box<dyn Write>>,
 }
 
 impl StdLib {
+    /// A standard library that prints to the process's stdout and stderr
     pub fn new() -> Self {
+        Self::with_output(Box::new(io::stdout()), Box::new(io::stderr()))
+    }
+
+    /// A standard library that sends `print` and `eprint` output to the given writers
+    pub fn with_output(output: Box<dyn Write>, error_output: Box<dyn Write>) -> Self {
+        Self::with_io(
+            Box::new(io::BufReader::new(io::stdin())),
+            output,
+            error_output,
+        )
+    }
+
+    /// A standard library that reads `input()` lines from `input` and writes to the given writers
+    pub fn with_io(
+        input: Box<dyn BufRead>,
+        output: Box<dyn Write>,
+        error_output: Box<dyn Write>,
+    ) -> Self {
         let mut functions: HashMap<&'static str, BuiltinFn> = HashMap::new();
+        let mut stateful_functions: HashMap<&'static str, StatefulFn> = HashMap::new();
 
         // Basic functions
-        functions.insert("input", |args: &[Value]| {
-            tunglang_input(
-                args.get(0)
-                    .unwrap_or(&Value::String(StringValue::new(String::new()))),
-            )
-        });
-        functions.insert("print", |args: &[Value]| {
-            std_print(
-                args.get(0)
-                    .unwrap_or(&Value::String(StringValue::new(String::new()))),
-            );
-            Value::Integer(Integer::new(0))
-        });
+        stateful_functions.insert("input", tunglang_input);
+        stateful_functions.insert("print", std_print);
+        stateful_functions.insert("eprint", std_eprint);
+        stateful_functions.insert("Error", errors::std_error);
+        stateful_functions.insert("assert", std_assert);
         functions.insert("abs", |args: &[Value]| {
-            std_abs(args.get(0).unwrap_or(&Value::Integer(Integer::new(0))))
+            std_abs(args.first().unwrap_or(&Value::Integer(Integer::new(0))))
         });
         functions.insert("len", |args: &[Value]| {
             std_len(
-                args.get(0)
+                args.first()
                     .unwrap_or(&Value::String(StringValue::new(String::new()))),
             )
         });
         functions.insert("range", std_range::std_range);
 
         // Type conversion functions (like Python)
-        functions.insert("int", |args: &[Value]| {
-            std_cast::std_int(args.get(0).unwrap_or(&Value::Integer(Integer::new(0))))
-        });
+        stateful_functions.insert("int", std_cast::std_int_call);
         functions.insert("str", |args: &[Value]| {
             std_cast::std_str(
-                args.get(0)
+                args.first()
                     .unwrap_or(&Value::String(StringValue::new(String::new()))),
             )
         });
-        functions.insert("float", |args: &[Value]| {
-            std_cast::std_float(args.get(0).unwrap_or(&Value::Integer(Integer::new(0))))
+        functions.insert("repr", |args: &[Value]| {
+            std_cast::std_repr(args.first().unwrap_or(&Value::Undefined))
         });
+        stateful_functions.insert("float", std_cast::std_float_call);
         functions.insert("bool", |args: &[Value]| {
-            std_cast::std_bool(args.get(0).unwrap_or(&Value::Integer(Integer::new(0))))
+            std_cast::std_bool(args.first().unwrap_or(&Value::Integer(Integer::new(0))))
         });
 
         // Math functions (like Python)
         functions.insert("min", std_math::std_min);
         functions.insert("max", std_math::std_max);
         functions.insert("sum", std_math::std_sum);
-        functions.insert("round", std_math::std_round);
+        stateful_functions.insert("round", std_math::std_round);
+        stateful_functions.insert("divmod", std_math::std_divmod);
+
+        // List functions (like Python), which run the method of the same name on their first
+        // argument: `append(xs, 1)` is `xs.append(1)`, returning the changed copy
+        stateful_functions.insert("append", method_function!("append"));
+        stateful_functions.insert("insert", method_function!("insert"));
+        stateful_functions.insert("pop", method_function!("pop"));
+        stateful_functions.insert("index", method_function!("index"));
+        stateful_functions.insert("sort", method_function!("sort"));
+
+        // Dict functions (like Python's dict methods)
+        stateful_functions.insert("keys", method_function!("keys"));
+        stateful_functions.insert("values", method_function!("values"));
+        stateful_functions.insert("items", method_function!("items"));
+        stateful_functions.insert("get", method_function!("get"));
+        stateful_functions.insert("remove", method_function!("remove"));
+        stateful_functions.insert("update", method_function!("update"));
+
+        // Set functions (like Python's set methods)
+        functions.insert("set", std_set::std_set);
+        stateful_functions.insert("add", method_function!("add"));
+
+        // String functions (like Python's str methods)
+        stateful_functions.insert("split", method_function!("split"));
+        stateful_functions.insert("join", method_function!("join"));
+        stateful_functions.insert("strip", method_function!("strip"));
+        stateful_functions.insert("lstrip", method_function!("lstrip"));
+        stateful_functions.insert("rstrip", method_function!("rstrip"));
+        stateful_functions.insert("replace", method_function!("replace"));
+        stateful_functions.insert("upper", method_function!("upper"));
+        stateful_functions.insert("lower", method_function!("lower"));
+        stateful_functions.insert("startswith", method_function!("startswith"));
+        stateful_functions.insert("endswith", method_function!("endswith"));
+        stateful_functions.insert("find", method_function!("find"));
+        stateful_functions.insert("count", method_function!("count"));
+        stateful_functions.insert("isdigit", method_function!("isdigit"));
+        stateful_functions.insert("isalpha", method_function!("isalpha"));
+        stateful_functions.insert("chars", method_function!("chars"));
+        stateful_functions.insert("format", method_function!("format"));
+
+        let prelude: StdModule = StdModule {
+            functions,
+            stateful_functions,
+            constants: HashMap::new(),
+        };
 
-        // List functions (like Python)
-        functions.insert("append", std_list::std_append);
-        functions.insert("insert", std_list::std_insert);
-        functions.insert("pop", std_list::std_pop);
-        functions.insert("index", std_list::std_index);
-        functions.insert("sort", std_list::std_sort);
+        // Modules group the built-ins, as in `import math` then `math.round(x)`. The functions
+        // that existed before modules did also stay global, so older programs keep working.
+        let mut std_modules: HashMap<&'static str, StdModule> = HashMap::new();
+        let mut math: StdModule = prelude.select(&["abs", "min", "max", "sum", "round", "divmod"]);
+        math.stateful_functions.insert("sqrt", std_math::std_sqrt);
+        math.stateful_functions.insert("pow", std_math::std_pow);
+        math.stateful_functions.insert("floor", std_math::std_floor);
+        math.stateful_functions.insert("ceil", std_math::std_ceil);
+        math.stateful_functions.insert("trunc", std_math::std_trunc);
+        math.stateful_functions.insert("sin", std_math::std_sin);
+        math.stateful_functions.insert("cos", std_math::std_cos);
+        math.stateful_functions.insert("tan", std_math::std_tan);
+        math.stateful_functions.insert("atan2", std_math::std_atan2);
+        math.stateful_functions.insert("log", std_math::std_log);
+        math.stateful_functions.insert("log10", std_math::std_log10);
+        math.stateful_functions.insert("exp", std_math::std_exp);
+        math.stateful_functions.insert("hypot", std_math::std_hypot);
+        math.stateful_functions.insert("gcd", std_math::std_gcd);
+        math.stateful_functions.insert("lcm", std_math::std_lcm);
+        math.stateful_functions
+            .insert("isclose", std_math::std_isclose);
+        let constants: [(&'static str, f64); 4] = [
+            ("pi", std::f64::consts::PI),
+            ("e", std::f64::consts::E),
+            ("inf", f64::INFINITY),
+            ("nan", f64::NAN),
+        ];
+        for (name, value) in constants {
+            math.constants
+                .insert(name, Value::FloatNumber(FloatNumber(value)));
+        }
+        std_modules.insert("math", math);
+        std_modules.insert(
+            "string",
+            prelude.select(&[
+                "split",
+                "join",
+                "strip",
+                "lstrip",
+                "rstrip",
+                "replace",
+                "upper",
+                "lower",
+                "startswith",
+                "endswith",
+                "find",
+                "count",
+                "isdigit",
+                "isalpha",
+                "chars",
+                "format",
+            ]),
+        );
+        std_modules.insert(
+            "list",
+            prelude.select(&["append", "insert", "pop", "index", "sort"]),
+        );
+        let mut os: StdModule = StdModule::default();
+        os.functions.insert("getenv", std_os::std_getenv);
+        os.functions.insert("cwd", std_os::std_cwd);
+        std_modules.insert("os", os);
+        let mut random: StdModule = StdModule::default();
+        let random_functions: [(&'static str, StatefulFn); 6] = [
+            ("seed", std_random::std_seed),
+            ("random", std_random::std_random),
+            ("randint", std_random::std_randint),
+            ("choice", std_random::std_choice),
+            ("shuffle", std_random::std_shuffle),
+            ("sample", std_random::std_sample),
+        ];
+        random.stateful_functions.extend(random_functions);
+        std_modules.insert("random", random);
 
-        Self { functions }
+        Self {
+            prelude,
+            std_modules,
+            methods: Methods::new(),
+            modules: Modules::from_env(),
+            rng: RefCell::new(Rng::from_time()),
+            input: RefCell::new(input),
+            output: RefCell::new(output),
+            error_output: RefCell::new(error_output),
+        }
     }
 
     pub fn get(&self, name: &str) -> Option<&BuiltinFn> {
-        self.functions.get(name)
+        self.prelude.get(name)
+    }
+
+    /// Looks up a built-in that needs the standard library itself, such as `print`
+    pub fn get_stateful(&self, name: &str) -> Option<&StatefulFn> {
+        self.prelude.get_stateful(name)
+    }
+
+    /// The built-ins every program can call without importing anything
+    pub fn prelude(&self) -> &StdModule {
+        &self.prelude
+    }
+
+    /// A standard library module, such as `math`
+    pub fn std_module(&self, name: &str) -> Option<&StdModule> {
+        self.std_modules.get(name)
+    }
+
+    /// What `import name` binds for a standard library module: a module whose members are its
+    /// functions and constants
+    pub fn std_module_value(&self, name: &str) -> Option<Value> {
+        let (module_name, module) = self.std_modules.get_key_value(name)?;
+        let mut members: Dict = module
+            .function_names()
+            .into_iter()
+            .map(|function| {
+                let value: Value = Value::BuiltinFunction {
+                    module: module_name,
+                    name: function,
+                };
+                (function.to_string(), value)
+            })
+            .collect();
+        for (constant, value) in &module.constants {
+            members.insert(constant.to_string(), value.clone());
+        }
+        Some(Value::Module {
+            name: module_name.to_string(),
+            members,
+        })
     }
+
+    /// The names of every built-in function, sorted: the prelude's, then each module's as
+    /// `module.function`
+    pub fn function_names(&self) -> Vec<String> {
+        let mut modules: Vec<(&&'static str, &StdModule)> = self.std_modules.iter().collect();
+        modules.sort_unstable_by_key(|(name, _)| **name);
+        let qualified = modules.into_iter().flat_map(|(module_name, module)| {
+            module
+                .function_names()
+                .into_iter()
+                .map(move |function| format!("{}.{}", module_name, function))
+        });
+        self.prelude
+            .function_names()
+            .into_iter()
+            .map(str::to_string)
+            .chain(qualified)
+            .collect()
+    }
+
+    /// Reads one line from the input stream without its line ending, or `None` at the end
+    pub fn read_line(&self) -> miette::Result<Option<String>> {
+        let mut line: String = String::new();
+        let read: usize = self
+            .input
+            .borrow_mut()
+            .read_line(&mut line)
+            .map_err(|e| miette::miette!(code = "IOError", "Error reading input: {}", e))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        let content_length: usize = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(content_length);
+        Ok(Some(line))
+    }
+
+    /// Writes text to the output stream and flushes it, so prompts appear before input is read
+    pub fn write_output(&self, text: &str) -> miette::Result<()> {
+        write_and_flush(&mut **self.output.borrow_mut(), text)
+    }
+
+    /// Writes text to the error stream
+    pub fn write_error_output(&self, text: &str) -> miette::Result<()> {
+        write_and_flush(&mut **self.error_output.borrow_mut(), text)
+    }
+
+    /// The program's source files and imported modules
+    pub fn modules(&self) -> &Modules {
+        &self.modules
+    }
+
+    /// This standard library, searching for imported modules with `modules` instead of
+    /// `TUNG_PATH`
+    pub fn with_modules(mut self, modules: Modules) -> Self {
+        self.modules = modules;
+        self
+    }
+
+    /// This standard library, with its random numbers starting from `seed` instead of the clock
+    pub fn with_seed(self, seed: u64) -> Self {
+        *self.rng.borrow_mut() = Rng::new(seed);
+        self
+    }
+
+    /// The generator behind the `random` module
+    pub fn rng(&self) -> RefMut<'_, Rng> {
+        self.rng.borrow_mut()
+    }
+
+    /// Looks up a built-in method such as `append` for the receiver's type
+    pub fn get_method(&self, receiver: &Value, name: &str) -> Option<&BuiltinMethod> {
+        self.methods.get(receiver, name)
+    }
+}
+
+impl Default for StdLib {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn write_and_flush(writer: &mut dyn Write, text: &str) -> miette::Result<()> {
+    writer
+        .write_all(text.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|e| miette::miette!(code = "IOError", "Error writing output: {}", e))
 }
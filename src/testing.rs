@@ -0,0 +1,135 @@
+// The TungLang test runner: finds `fn test_*` functions and runs each one on its own
+use crate::eval::functions::{call_function, CallArguments};
+use crate::interpreter::execute_program;
+use crate::output::OutputBuffer;
+use crate::parser::Rule;
+use crate::stdlib::StdLib;
+use crate::value::Value;
+use pest::iterators::{Pair, Pairs};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Test functions are the top-level functions whose names start with this
+pub const TEST_PREFIX: &str = "test_";
+
+/// How one test function went
+pub struct TestResult {
+    pub name: String,
+    /// The error that failed the test, or `None` if it passed
+    pub error: Option<miette::Report>,
+    /// Everything the test printed, to stdout and stderr alike
+    pub output: String,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The `.tung` files to test: files given directly, and every `.tung` file under the given
+/// directories except in hidden directories and `target`, in a stable order
+pub fn discover(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            collect_files(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn collect_files(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    entries.sort();
+    for entry in entries {
+        let name: &str = entry.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if entry.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                collect_files(&entry, files)?;
+            }
+        } else if is_tung_file(&entry) {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// Whether a path names a TungLang source file
+pub fn is_tung_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("tung"))
+}
+
+/// The top-level test functions of a parsed program, in the order they are defined
+pub fn test_functions(program: Pairs<'static, Rule>) -> Vec<Pair<'static, Rule>> {
+    program
+        .filter(|pair| pair.as_rule() == Rule::function_definition)
+        .filter(|pair| {
+            pair.clone()
+                .into_inner()
+                .next()
+                .is_some_and(|name| name.as_str().starts_with(TEST_PREFIX))
+        })
+        .collect()
+}
+
+/// Runs every test function of a program read from `file`, if it came from one. Each test gets a
+/// fresh interpreter: the whole program runs again, then the test function is called, so tests
+/// cannot see each other's changes. With a `seed`, every test starts its random numbers from it.
+pub fn run_tests(
+    program: Pairs<'static, Rule>,
+    file: Option<&Path>,
+    seed: Option<u64>,
+) -> Vec<TestResult> {
+    test_functions(program.clone())
+        .into_iter()
+        .map(|definition| run_test(program.clone(), definition, file, seed))
+        .collect()
+}
+
+fn run_test(
+    program: Pairs<'static, Rule>,
+    definition: Pair<'static, Rule>,
+    file: Option<&Path>,
+    seed: Option<u64>,
+) -> TestResult {
+    let name: String = definition
+        .clone()
+        .into_inner()
+        .next()
+        .unwrap()
+        .as_str()
+        .to_string();
+    let output: OutputBuffer = OutputBuffer::new();
+    let mut stdlib: StdLib =
+        StdLib::with_output(Box::new(output.clone()), Box::new(output.clone()));
+    if let Some(seed) = seed {
+        stdlib = stdlib.with_seed(seed);
+    }
+    if let Some(file) = file {
+        stdlib.modules().register_entry(program.get_input(), file);
+    }
+    let mut variables: HashMap<String, Value> = HashMap::new();
+    let outcome: miette::Result<Value> = execute_program(program, &mut variables, &stdlib)
+        .and_then(|()| {
+            let function: Value = variables.get(&name).cloned().unwrap_or(Value::Undefined);
+            let arguments: CallArguments = CallArguments {
+                positional: Vec::new(),
+                keyword: Vec::new(),
+            };
+            call_function(&name, &function, arguments, definition.as_span(), &stdlib)
+        });
+    TestResult {
+        name,
+        error: outcome.err(),
+        output: output.contents(),
+    }
+}
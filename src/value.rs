@@ -1,4 +1,9 @@
+use crate::format::{format_float, FormatSpec};
+use crate::parser::Rule;
+use pest::iterators::Pair;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 use std::hash::{Hash, Hasher};
@@ -11,51 +16,47 @@ impl Integer {
     pub fn new(value: i64) -> Self {
         Integer(value)
     }
-}
-
-impl Add for Integer {
-    type Output = Integer;
-    fn add(self, rhs: Integer) -> Integer {
-        Integer(self.0 + rhs.0)
+    // Checked arithmetic: `None` means the result does not fit in an i64, or the divisor is zero
+    pub fn checked_add(self, rhs: Integer) -> Option<Integer> {
+        self.0.checked_add(rhs.0).map(Integer)
     }
-}
-impl Sub for Integer {
-    type Output = Integer;
-    fn sub(self, rhs: Integer) -> Integer {
-        Integer(self.0 - rhs.0)
+    pub fn checked_sub(self, rhs: Integer) -> Option<Integer> {
+        self.0.checked_sub(rhs.0).map(Integer)
     }
-}
-impl Mul for Integer {
-    type Output = Integer;
-    fn mul(self, rhs: Integer) -> Integer {
-        Integer(self.0 * rhs.0)
+    pub fn checked_mul(self, rhs: Integer) -> Option<Integer> {
+        self.0.checked_mul(rhs.0).map(Integer)
     }
-}
-impl Div for Integer {
-    type Output = Integer;
-    fn div(self, rhs: Integer) -> Integer {
-        Integer(self.0 / rhs.0)
+    /// Division rounded towards negative infinity, like Python's `//`
+    pub fn checked_floor_div(self, rhs: Integer) -> Option<Integer> {
+        let quotient: i64 = self.0.checked_div(rhs.0)?;
+        let remainder: i64 = self.0.checked_rem(rhs.0)?;
+        if remainder != 0 && (remainder < 0) != (rhs.0 < 0) {
+            Some(Integer(quotient - 1))
+        } else {
+            Some(Integer(quotient))
+        }
     }
-}
-impl Rem for Integer {
-    type Output = Integer;
-    fn rem(self, rhs: Integer) -> Integer {
-        Integer(self.0 % rhs.0)
+    /// Remainder with the sign of the divisor, like Python's `%`
+    pub fn checked_floor_mod(self, rhs: Integer) -> Option<Integer> {
+        let remainder: i64 = self.0.checked_rem(rhs.0)?;
+        if remainder != 0 && (remainder < 0) != (rhs.0 < 0) {
+            Some(Integer(remainder + rhs.0))
+        } else {
+            Some(Integer(remainder))
+        }
     }
-}
-impl Neg for Integer {
-    type Output = Integer;
-    fn neg(self) -> Integer {
-        Integer(-self.0)
+    pub fn checked_neg(self) -> Option<Integer> {
+        self.0.checked_neg().map(Integer)
     }
 }
+
 impl std::fmt::Display for Integer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy)]
 pub struct FloatNumber(pub f64);
 
 impl FloatNumber {
@@ -102,19 +103,37 @@ impl Neg for FloatNumber {
 }
 impl std::fmt::Display for FloatNumber {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", format_float(self.0, &FormatSpec::default()))
+    }
+}
+/// Unlike `f64`, NaN equals NaN, so floats can be dict keys and set items like any other value
+impl PartialEq for FloatNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0 || (self.0.is_nan() && other.0.is_nan())
     }
 }
 impl Eq for FloatNumber {}
+impl PartialOrd for FloatNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 impl Ord for FloatNumber {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
     }
 }
 impl Hash for FloatNumber {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        // Not ideal for NaN, but works for most cases
-        state.write_u64(self.0.to_bits());
+        // Values that compare equal must hash the same: 0.0 and -0.0, and every NaN
+        let canonical: f64 = if self.0 == 0.0 {
+            0.0
+        } else if self.0.is_nan() {
+            f64::NAN
+        } else {
+            self.0
+        };
+        state.write_u64(canonical.to_bits());
     }
 }
 
@@ -128,8 +147,12 @@ impl StringValue {
     pub fn repeat(&self, n: usize) -> StringValue {
         StringValue(self.0.repeat(n))
     }
+    /// Length in characters, not bytes, so `len("héllo")` is 5
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.0.chars().count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
 }
 impl std::ops::Add<&StringValue> for StringValue {
@@ -165,59 +188,347 @@ impl std::fmt::Display for BooleanValue {
 }
 
 pub type Array = Vec<Value>;
+
+/// Variables in a scope, keyed by name. Also used for struct method tables.
 pub type Dict = HashMap<String, Value>;
 
+/// A TungLang dict: keys are any hashable value, and iteration follows insertion order
+#[derive(Debug, Clone, Default)]
+pub struct DictValue {
+    entries: Vec<(Value, Value)>,
+    index: HashMap<Value, usize>,
+}
+
+impl DictValue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+    pub fn contains_key(&self, key: &Value) -> bool {
+        self.index.contains_key(key)
+    }
+    /// Inserts or replaces a value. A replaced key keeps its original position.
+    /// The caller is responsible for checking the key is hashable.
+    pub fn insert(&mut self, key: Value, value: Value) -> Option<Value> {
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+    /// Removes a key, keeping the remaining entries in order
+    pub fn remove(&mut self, key: &Value) -> Option<Value> {
+        let i: usize = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for position in self.index.values_mut() {
+            if *position > i {
+                *position -= 1;
+            }
+        }
+        Some(value)
+    }
+    /// Inserts every entry of `other`, like Python's `dict.update`
+    pub fn update(&mut self, other: &DictValue) {
+        for (key, value) in other.iter() {
+            self.insert(key.clone(), value.clone());
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&Value, &Value)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &Value> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
+
+/// A TungLang set: unique hashable values, iterated in insertion order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetValue(DictValue);
+
+impl SetValue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn contains(&self, item: &Value) -> bool {
+        self.0.contains_key(item)
+    }
+    /// Adds an item, returning whether it was new. The caller checks the item is hashable.
+    pub fn insert(&mut self, item: Value) -> bool {
+        self.0.insert(item, Value::Undefined).is_none()
+    }
+    pub fn remove(&mut self, item: &Value) -> bool {
+        self.0.remove(item).is_some()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.0.keys()
+    }
+    pub fn is_subset(&self, other: &SetValue) -> bool {
+        self.iter().all(|item| other.contains(item))
+    }
+    pub fn union(&self, other: &SetValue) -> SetValue {
+        let mut result: SetValue = self.clone();
+        for item in other.iter() {
+            result.insert(item.clone());
+        }
+        result
+    }
+    pub fn intersection(&self, other: &SetValue) -> SetValue {
+        self.iter()
+            .filter(|item| other.contains(item))
+            .cloned()
+            .collect()
+    }
+    pub fn difference(&self, other: &SetValue) -> SetValue {
+        self.iter()
+            .filter(|item| !other.contains(item))
+            .cloned()
+            .collect()
+    }
+    pub fn symmetric_difference(&self, other: &SetValue) -> SetValue {
+        let mut result: SetValue = self.difference(other);
+        for item in other.iter().filter(|item| !self.contains(item)) {
+            result.insert(item.clone());
+        }
+        result
+    }
+}
+
+impl FromIterator<Value> for SetValue {
+    fn from_iter<I: IntoIterator<Item = Value>>(items: I) -> Self {
+        let mut set: SetValue = SetValue::new();
+        for item in items {
+            set.insert(item);
+        }
+        set
+    }
+}
+
+/// Dicts are equal when they hold the same entries, whatever order they were inserted in
+impl PartialEq for DictValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+/// A user function parameter: `name`, `name = default` or `*name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub default: Option<Value>,
+    pub variadic: bool,
+}
+
+/// The methods of a struct type. The type and every instance built from it share one table, so
+/// an `impl` block reaches instances made before it ran, wherever they are used.
+#[derive(Clone, Default)]
+pub struct MethodTable(Rc<RefCell<Dict>>);
+
+impl MethodTable {
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.0.borrow().get(name).cloned()
+    }
+    pub fn extend(&self, methods: Dict) {
+        self.0.borrow_mut().extend(methods);
+    }
+}
+// Methods capture the scope holding their own type, so tables compare and print by identity
+// rather than by walking into that cycle
+impl PartialEq for MethodTable {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl std::fmt::Debug for MethodTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.borrow().keys()).finish()
+    }
+}
+
+/// A struct instance: the struct's name, its field values in declaration order and its type's
+/// methods
 #[derive(Debug, Clone, PartialEq)]
+pub struct StructValue {
+    pub name: String,
+    pub fields: Vec<(String, Value)>,
+    pub methods: MethodTable,
+}
+
+impl StructValue {
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value)
+    }
+    pub fn get_mut(&mut self, field: &str) -> Option<&mut Value> {
+        self.fields
+            .iter_mut()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value)
+    }
+}
+#[derive(Debug, Clone)]
 pub enum Value {
     Integer(Integer),
+    /// An integer outside the i64 range; only exists with the `bigint` feature
+    #[cfg(feature = "bigint")]
+    BigInteger(num_bigint::BigInt),
     FloatNumber(FloatNumber),
     String(StringValue),
     Boolean(BooleanValue),
     Array(Array),
-    Dict(Dict),
+    Tuple(Array),
+    Dict(DictValue),
+    Set(SetValue),
+    /// `none`: the result of a function that returns nothing, or a missing value
     Undefined,
     Function {
-        parameters: Vec<String>,
-        body: String,
+        /// The name it was defined with, bound again inside its body so it can call itself
+        /// however it is called
+        name: String,
+        parameters: Vec<Parameter>,
+        body: Pair<'static, Rule>,
         environment: Dict,
     },
+    Struct(StructValue),
+    StructType {
+        name: String,
+        fields: Vec<String>,
+        methods: MethodTable,
+    },
+    /// An imported module: the top-level names its file defined, read with `module.name`
+    Module {
+        name: String,
+        members: Dict,
+    },
+    /// A function of a standard library module, such as `math.round`
+    BuiltinFunction {
+        module: &'static str,
+        name: &'static str,
+    },
 }
 
 pub type BuiltinFn = fn(args: &[Value]) -> Value;
 
-impl std::fmt::Display for Value {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Value {
+    /// The user-facing name of this value's type, used in error messages and method dispatch
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Integer(_) => "int",
+            #[cfg(feature = "bigint")]
+            Value::BigInteger(_) => "int",
+            Value::FloatNumber(_) => "float",
+            Value::String(_) => "string",
+            Value::Boolean(_) => "bool",
+            Value::Array(_) => "array",
+            Value::Tuple(_) => "tuple",
+            Value::Dict(_) => "dict",
+            Value::Set(_) => "set",
+            Value::Undefined => "none",
+            Value::Function { .. } => "function",
+            Value::Struct(_) => "struct",
+            Value::StructType { .. } => "struct type",
+            Value::Module { .. } => "module",
+            Value::BuiltinFunction { .. } => "built-in function",
+        }
+    }
+}
+
+impl Value {
+    /// Whether this value can be used as a dict key: tuples are hashable when their items are
+    pub fn is_hashable(&self) -> bool {
+        match self {
+            Value::Integer(_)
+            | Value::FloatNumber(_)
+            | Value::String(_)
+            | Value::Boolean(_)
+            | Value::Undefined => true,
+            #[cfg(feature = "bigint")]
+            Value::BigInteger(_) => true,
+            Value::Tuple(items) => items.iter().all(Value::is_hashable),
+            _ => false,
+        }
+    }
+}
+
+/// Values are equal as dict keys, set items and for `in` when `==` says so, except that NaN
+/// matches NaN; see `eval::compare`
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        crate::eval::compare::same_value(self, other)
+    }
+}
+
+impl Eq for Value {}
+
+/// Only hashable values are used as dict keys; the rest hash by type alone. Equal numbers hash
+/// the same whatever their type, so `1`, `1.0` and a big integer are one key.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
         match self {
-            Value::Integer(Integer(n)) => write!(f, "{}", n),
-            Value::FloatNumber(FloatNumber(n)) => write!(f, "{}", n),
-            Value::String(StringValue(s)) => write!(f, "{}", s),
-            Value::Boolean(BooleanValue(b)) => write!(f, "{}", b),
-            Value::Array(array) => {
-                write!(f, "[")?;
-                let mut is_first = true;
-                for element in array {
-                    if !is_first {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "{}", element)?;
-                    is_first = false;
+            Value::Integer(n) => hash_integer(n.0, state),
+            Value::FloatNumber(f) if f.0.fract() == 0.0 => {
+                if f.0 >= i64::MIN as f64 && f.0 < i64::MAX as f64 {
+                    hash_integer(f.0 as i64, state)
+                } else {
+                    #[cfg(feature = "bigint")]
+                    hash_big_integer(&num_bigint::ToBigInt::to_bigint(&f.0).unwrap(), state);
+                    #[cfg(not(feature = "bigint"))]
+                    f.hash(state);
                 }
-                write!(f, "]")
             }
-            Value::Dict(dict) => {
-                write!(f, "{{")?;
-                let mut is_first = true;
-                for (key, value) in dict {
-                    if !is_first {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "\"{}\": {}", key, value)?;
-                    is_first = false;
+            #[cfg(feature = "bigint")]
+            Value::BigInteger(n) => hash_big_integer(n, state),
+            _ => {
+                std::mem::discriminant(self).hash(state);
+                match self {
+                    Value::FloatNumber(f) => f.hash(state),
+                    Value::String(s) => s.hash(state),
+                    Value::Boolean(b) => b.hash(state),
+                    Value::Tuple(items) => items.hash(state),
+                    _ => {}
                 }
-                write!(f, "}}")
             }
-            Value::Undefined => write!(f, "undefined"),
-            Value::Function { .. } => write!(f, "Function"),
         }
     }
 }
+
+fn hash_integer<H: Hasher>(n: i64, state: &mut H) {
+    "int".hash(state);
+    n.hash(state);
+}
+
+#[cfg(feature = "bigint")]
+fn hash_big_integer<H: Hasher>(n: &num_bigint::BigInt, state: &mut H) {
+    "bigint".hash(state);
+    n.hash(state);
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", crate::format::to_str(self))
+    }
+}
@@ -0,0 +1,98 @@
+// Checks a parsed program for likely mistakes before it runs. Problems found here are warnings:
+// the program still runs, but the user is told where it may not do what they meant.
+use crate::parser::Rule;
+use miette::{LabeledSpan, Severity};
+use pest::iterators::{Pair, Pairs};
+
+/// Warnings for the whole program, in source order
+pub fn check_program(program: Pairs<'static, Rule>) -> Vec<miette::Report> {
+    let blocks = program
+        .clone()
+        .flatten()
+        .filter(|pair: &Pair<Rule>| pair.as_rule() == Rule::block)
+        .map(|block: Pair<Rule>| block.into_inner());
+    let mut warnings: Vec<(usize, miette::Report)> = std::iter::once(program.clone())
+        .chain(blocks)
+        .flat_map(check_slash_comments)
+        .collect();
+    warnings.extend(
+        program
+            .flatten()
+            .filter(|pair: &Pair<Rule>| pair.as_rule() == Rule::match_statement)
+            .filter_map(check_bool_match),
+    );
+    warnings.sort_by_key(|(offset, _)| *offset);
+    warnings.into_iter().map(|(_, warning)| warning).collect()
+}
+
+/// `//` used to start a comment, and an old comment often still parses: `print(x) // show x`
+/// divides by `show`, and `x` is the next statement. A statement starting on the line of a `//`
+/// in the one before it gives that away.
+fn check_slash_comments(statements: Pairs<'static, Rule>) -> Vec<(usize, miette::Report)> {
+    let statements: Vec<Pair<Rule>> = statements
+        .filter(|pair: &Pair<Rule>| pair.as_rule() != Rule::EOI)
+        .collect();
+    statements
+        .windows(2)
+        .filter_map(|pair: &[Pair<Rule>]| {
+            let line: usize = pair[1].line_col().0;
+            let slashes: Pair<Rule> = pair[0]
+                .clone()
+                .into_inner()
+                .flatten()
+                .filter(|op: &Pair<Rule>| {
+                    op.as_rule() == Rule::mul_op && op.as_str() == "//" && op.line_col().0 == line
+                })
+                .last()?;
+            let span = slashes.as_span();
+            let warning: miette::Report = miette::miette!(
+                severity = Severity::Warning,
+                labels = vec![LabeledSpan::at(
+                    span.start()..span.end(),
+                    "this divides by what follows it"
+                )],
+                help = "line comments start with `#`; `//` is floor division",
+                "Warning: This `//` looks like a comment, but it is floor division."
+            );
+            Some((span.start(), warning))
+        })
+        .collect()
+}
+
+/// A `match` with `true` or `false` arms should handle both, or have a `_` arm
+fn check_bool_match(statement: Pair<'static, Rule>) -> Option<(usize, miette::Report)> {
+    let mut inner: Pairs<Rule> = statement.into_inner();
+    let span = inner.next().unwrap().as_span();
+    let patterns: Vec<Pair<Rule>> = inner
+        .filter(|pair: &Pair<Rule>| pair.as_rule() == Rule::match_arm)
+        .flat_map(|arm: Pair<Rule>| arm.into_inner())
+        .collect();
+    if patterns.iter().any(|pattern: &Pair<Rule>| {
+        matches!(
+            pattern.as_rule(),
+            Rule::wildcard_pattern | Rule::capture_pattern
+        )
+    }) {
+        return None;
+    }
+    let handles = |constant: &str| {
+        patterns.iter().any(|pattern: &Pair<Rule>| {
+            pattern.as_rule() == Rule::literal_pattern && pattern.as_str() == constant
+        })
+    };
+    let missing: &str = match (handles("true"), handles("false")) {
+        (true, false) => "false",
+        (false, true) => "true",
+        _ => return None,
+    };
+    let warning: miette::Report = miette::miette!(
+        severity = Severity::Warning,
+        labels = vec![LabeledSpan::at(
+            span.start()..span.end(),
+            format!("`{}` is not handled", missing)
+        )],
+        help = format!("add a `{}` arm, or a `_` arm for everything else", missing),
+        "Warning: This match on a bool is not exhaustive."
+    );
+    Some((span.start(), warning))
+}
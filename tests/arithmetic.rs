@@ -0,0 +1,320 @@
+mod common;
+
+use common::{error_message, parse, variables};
+use std::collections::HashMap;
+use tung_lang::format::to_repr;
+use tung_lang::parser::{syntax_error, Rule};
+use tung_lang::value::{FloatNumber, Integer, Value};
+use tung_lang::warnings::check_program;
+use tung_lang::{Parser, TungParser};
+
+#[test]
+fn arithmetic_near_the_limits() {
+    let variables = variables(
+        r#"
+        var max = 9223372036854775806 + 1
+        var min = 0 - 9223372036854775807 - 1
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["max"], Value::Integer(Integer(i64::MAX)));
+    assert_eq!(variables["min"], Value::Integer(Integer(i64::MIN)));
+}
+
+#[test]
+#[cfg(not(feature = "bigint"))]
+fn overflow_is_an_error() {
+    assert_eq!(
+        error_message("var x = 9223372036854775807 + 1"),
+        "Error: Integer overflow: 9223372036854775807 + 1 does not fit in 64 bits."
+    );
+    assert_eq!(
+        error_message("var x = 0 - 9223372036854775807 - 2"),
+        "Error: Integer overflow: -9223372036854775807 - 2 does not fit in 64 bits."
+    );
+    assert_eq!(
+        error_message("var x = 4611686018427387904 * 2"),
+        "Error: Integer overflow: 4611686018427387904 * 2 does not fit in 64 bits."
+    );
+}
+
+#[test]
+#[cfg(not(feature = "bigint"))]
+fn augmented_assignment_overflow_is_an_error() {
+    assert_eq!(
+        error_message(
+            r#"
+            var x = 9223372036854775807
+            x += 1
+        "#
+        ),
+        "Error: Integer overflow: 9223372036854775807 + 1 does not fit in 64 bits."
+    );
+}
+
+#[test]
+fn integer_powers_are_exact() {
+    let variables = variables(
+        r#"
+        var small = 3 ** 4
+        var large = 2 ** 62
+        var right = 2 ** 3 ** 2
+        var negative = 2 ** (0 - 2)
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["small"], Value::Integer(Integer(81)));
+    assert_eq!(variables["large"], Value::Integer(Integer(1 << 62)));
+    assert_eq!(variables["right"], Value::Integer(Integer(512)));
+    assert_eq!(variables["negative"], Value::FloatNumber(FloatNumber(0.25)));
+}
+
+#[test]
+#[cfg(not(feature = "bigint"))]
+fn power_overflow_is_an_error() {
+    assert_eq!(
+        error_message("var x = 2 ** 63"),
+        "Error: Integer overflow: 2 ** 63 does not fit in 64 bits."
+    );
+}
+
+#[test]
+fn division_by_zero_is_an_error() {
+    assert_eq!(error_message("var x = 1 / 0"), "Error: Division by zero.");
+    assert_eq!(
+        error_message("var x = 1.5 / 0.0"),
+        "Error: Division by zero."
+    );
+    assert_eq!(
+        error_message(
+            r#"
+            var x = 5
+            x %= 0
+        "#
+        ),
+        "Error: Division by zero."
+    );
+}
+
+#[test]
+#[cfg(not(feature = "bigint"))]
+fn oversized_literal_is_an_error() {
+    assert_eq!(
+        error_message("var x = 9223372036854775808"),
+        "Error: Integer literal 9223372036854775808 does not fit in 64 bits."
+    );
+}
+
+/// Results from CPython 3 for the same expressions (written `0 - x` since there is no unary minus)
+const PYTHON_RESULTS: &[(&str, &str)] = &[
+    ("7 // 2", "3"),
+    ("7 % 2", "1"),
+    ("7 // (0 - 2)", "-4"),
+    ("7 % (0 - 2)", "-1"),
+    ("7 // 3", "2"),
+    ("7 % 3", "1"),
+    ("7 // (0 - 3)", "-3"),
+    ("7 % (0 - 3)", "-2"),
+    ("(0 - 7) // 2", "-4"),
+    ("(0 - 7) % 2", "1"),
+    ("(0 - 7) // (0 - 2)", "3"),
+    ("(0 - 7) % (0 - 2)", "-1"),
+    ("(0 - 7) // 3", "-3"),
+    ("(0 - 7) % 3", "2"),
+    ("(0 - 7) // (0 - 3)", "2"),
+    ("(0 - 7) % (0 - 3)", "-1"),
+    ("6 // 2", "3"),
+    ("6 % 2", "0"),
+    ("6 // (0 - 2)", "-3"),
+    ("6 % (0 - 2)", "0"),
+    ("6 // 3", "2"),
+    ("6 % 3", "0"),
+    ("6 // (0 - 3)", "-2"),
+    ("6 % (0 - 3)", "0"),
+    ("(0 - 6) // 2", "-3"),
+    ("(0 - 6) % 2", "0"),
+    ("(0 - 6) // (0 - 2)", "3"),
+    ("(0 - 6) % (0 - 2)", "0"),
+    ("(0 - 6) // 3", "-2"),
+    ("(0 - 6) % 3", "0"),
+    ("(0 - 6) // (0 - 3)", "2"),
+    ("(0 - 6) % (0 - 3)", "0"),
+    ("1 // 2", "0"),
+    ("1 % 2", "1"),
+    ("1 // (0 - 2)", "-1"),
+    ("1 % (0 - 2)", "-1"),
+    ("1 // 3", "0"),
+    ("1 % 3", "1"),
+    ("1 // (0 - 3)", "-1"),
+    ("1 % (0 - 3)", "-2"),
+    ("0 // 2", "0"),
+    ("0 % 2", "0"),
+    ("0 // (0 - 2)", "0"),
+    ("0 % (0 - 2)", "0"),
+    ("0 // 3", "0"),
+    ("0 % 3", "0"),
+    ("0 // (0 - 3)", "0"),
+    ("0 % (0 - 3)", "0"),
+    ("7.5 // 2.0", "3.0"),
+    ("7.5 % 2.0", "1.5"),
+    ("7.5 // (0 - 2.0)", "-4.0"),
+    ("7.5 % (0 - 2.0)", "-0.5"),
+    ("7.5 // 0.5", "15.0"),
+    ("7.5 % 0.5", "0.0"),
+    ("7.5 // (0 - 0.75)", "-10.0"),
+    ("7.5 % (0 - 0.75)", "-0.0"),
+    ("7.5 // 2", "3.0"),
+    ("7.5 % 2", "1.5"),
+    ("7.5 // (0 - 2)", "-4.0"),
+    ("7.5 % (0 - 2)", "-0.5"),
+    ("(0 - 7.5) // 2.0", "-4.0"),
+    ("(0 - 7.5) % 2.0", "0.5"),
+    ("(0 - 7.5) // (0 - 2.0)", "3.0"),
+    ("(0 - 7.5) % (0 - 2.0)", "-1.5"),
+    ("(0 - 7.5) // 0.5", "-15.0"),
+    ("(0 - 7.5) % 0.5", "0.0"),
+    ("(0 - 7.5) // (0 - 0.75)", "10.0"),
+    ("(0 - 7.5) % (0 - 0.75)", "-0.0"),
+    ("(0 - 7.5) // 2", "-4.0"),
+    ("(0 - 7.5) % 2", "0.5"),
+    ("(0 - 7.5) // (0 - 2)", "3.0"),
+    ("(0 - 7.5) % (0 - 2)", "-1.5"),
+    ("0.0 // 2.0", "0.0"),
+    ("0.0 % 2.0", "0.0"),
+    ("0.0 // (0 - 2.0)", "-0.0"),
+    ("0.0 % (0 - 2.0)", "-0.0"),
+    ("0.0 // 0.5", "0.0"),
+    ("0.0 % 0.5", "0.0"),
+    ("0.0 // (0 - 0.75)", "-0.0"),
+    ("0.0 % (0 - 0.75)", "-0.0"),
+    ("0.0 // 2", "0.0"),
+    ("0.0 % 2", "0.0"),
+    ("0.0 // (0 - 2)", "-0.0"),
+    ("0.0 % (0 - 2)", "-0.0"),
+    ("(0 - 0.0) // 2.0", "0.0"),
+    ("(0 - 0.0) % 2.0", "0.0"),
+    ("(0 - 0.0) // (0 - 2.0)", "-0.0"),
+    ("(0 - 0.0) % (0 - 2.0)", "-0.0"),
+    ("(0 - 0.0) // 0.5", "0.0"),
+    ("(0 - 0.0) % 0.5", "0.0"),
+    ("(0 - 0.0) // (0 - 0.75)", "-0.0"),
+    ("(0 - 0.0) % (0 - 0.75)", "-0.0"),
+    ("(0 - 0.0) // 2", "0.0"),
+    ("(0 - 0.0) % 2", "0.0"),
+    ("(0 - 0.0) // (0 - 2)", "-0.0"),
+    ("(0 - 0.0) % (0 - 2)", "-0.0"),
+    ("1.25 // 2.0", "0.0"),
+    ("1.25 % 2.0", "1.25"),
+    ("1.25 // (0 - 2.0)", "-1.0"),
+    ("1.25 % (0 - 2.0)", "-0.75"),
+    ("1.25 // 0.5", "2.0"),
+    ("1.25 % 0.5", "0.25"),
+    ("1.25 // (0 - 0.75)", "-2.0"),
+    ("1.25 % (0 - 0.75)", "-0.25"),
+    ("1.25 // 2", "0.0"),
+    ("1.25 % 2", "1.25"),
+    ("1.25 // (0 - 2)", "-1.0"),
+    ("1.25 % (0 - 2)", "-0.75"),
+    ("7 // 2.0", "3.0"),
+    ("7 % 2.0", "1.0"),
+    ("7 // (0 - 0.5)", "-14.0"),
+    ("7 % (0 - 0.5)", "-0.0"),
+    ("(0 - 7) // 2.0", "-4.0"),
+    ("(0 - 7) % 2.0", "1.0"),
+    ("(0 - 7) // (0 - 0.5)", "14.0"),
+    ("(0 - 7) % (0 - 0.5)", "-0.0"),
+    ("divmod(7, 2)", "(3, 1)"),
+    ("divmod(0 - 7, 2)", "(-4, 1)"),
+    ("divmod(7, 0 - 2)", "(-4, -1)"),
+    ("divmod(0 - 7.5, 2)", "(-4.0, 0.5)"),
+    ("divmod(7.5, 0 - 2)", "(-4.0, -0.5)"),
+    ("divmod(1, 0.25)", "(4.0, 0.0)"),
+    ("2 ** 10", "1024"),
+    ("(0 - 2) ** 3", "-8"),
+    ("(0 - 2) ** 4", "16"),
+    ("2 ** (0 - 2)", "0.25"),
+    ("(0 - 2) ** (0 - 3)", "-0.125"),
+    ("0 ** 0", "1"),
+    ("10 ** 18", "1000000000000000000"),
+    ("2.0 ** 3", "8.0"),
+    ("2 ** 0.5", "1.4142135623730951"),
+    ("4 ** (0 - 0.5)", "0.5"),
+    ("1 ** 100", "1"),
+    ("(0 - 1) ** 101", "-1"),
+    ("1 ** 5000000000", "1"),
+    ("0 ** 5000000000", "0"),
+    ("(0 - 1) ** 5000000000", "1"),
+    ("(0 - 1) ** 5000000001", "-1"),
+    ("1 ** 9223372036854775807", "1"),
+];
+
+#[test]
+fn floor_division_modulo_and_power_match_python() {
+    for (expression, expected) in PYTHON_RESULTS {
+        let source: &'static str =
+            Box::leak(format!("var result = {}", expression).into_boxed_str());
+        let variables =
+            variables(source).unwrap_or_else(|e| panic!("{} failed: {}", expression, e));
+        assert_eq!(to_repr(&variables["result"]), *expected, "{}", expression);
+    }
+}
+
+#[test]
+fn floor_division_by_zero_is_an_error() {
+    assert_eq!(error_message("var x = 7 // 0"), "Error: Division by zero.");
+    assert_eq!(
+        error_message("var x = 7.5 % 0.0"),
+        "Error: Division by zero."
+    );
+    assert_eq!(
+        error_message("var x = divmod(1, 0)"),
+        "Error: Division by zero."
+    );
+    assert_eq!(
+        error_message("var x = 0 ** (0 - 1)"),
+        "Error: Division by zero."
+    );
+}
+
+#[test]
+fn double_slash_divides_so_comments_start_with_hash() {
+    let vars: HashMap<String, Value> =
+        variables("var x = 7 // 2 # halved\n/* a block\ncomment */\nvar y = 100 // 7 // 2")
+            .unwrap();
+    assert_eq!(vars["x"], Value::Integer(Integer(3)));
+    assert_eq!(vars["y"], Value::Integer(Integer(7)));
+    assert!(check_program(parse("var z = 7 // 2\nprint(z // 2)")).is_empty());
+}
+
+/// The help a parse error gets, if any
+fn syntax_help(source: &str) -> Option<String> {
+    let error = TungParser::parse(Rule::program, source).unwrap_err();
+    let report = syntax_error(miette::diagnostic!("Error parsing program"), source, &error);
+    report.help().map(|help| help.to_string())
+}
+
+#[test]
+fn old_double_slash_comments_point_to_hash() {
+    // Most old comments do not parse, and the error says to write `#`
+    let help = "line comments start with `#`; `//` is floor division".to_string();
+    assert_eq!(syntax_help("// a comment\nvar x = 1"), Some(help.clone()));
+    assert_eq!(
+        syntax_help("var x = 1\nprint(x) // show x"),
+        Some(help.clone())
+    );
+    assert_eq!(syntax_help("var x = 1 // the answer: 1"), Some(help));
+    assert_eq!(syntax_help("var s = \"a // b\" +"), None);
+    // One that does parse runs into a NameError, so it gets a warning before the program runs
+    let warnings: Vec<String> = check_program(parse("var x = 4\nvar y = x // show x\nprint(y)"))
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect();
+    assert_eq!(
+        warnings,
+        ["Warning: This `//` looks like a comment, but it is floor division."]
+    );
+    assert_eq!(
+        check_program(parse("fn f(x) {\n    return x // half of it\n}")).len(),
+        1
+    );
+}
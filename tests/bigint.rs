@@ -0,0 +1,101 @@
+#![cfg(feature = "bigint")]
+
+mod common;
+
+use common::{execute, Run};
+use std::collections::HashMap;
+use tung_lang::value::{Integer, Value};
+
+/// Runs a program, returning what it printed and its variables
+fn run(source: &'static str) -> (String, HashMap<String, Value>) {
+    let run: Run = execute(source, "", |stdlib| stdlib);
+    run.result.unwrap();
+    (run.output, run.variables)
+}
+
+#[test]
+fn overflow_promotes_to_big_integer() {
+    let (out, _) = run(r#"
+        var x = 9223372036854775807
+        x += 1
+        print(x, 9223372036854775807 * 2, 0 - 9223372036854775807 - 2)
+    "#);
+    assert_eq!(
+        out,
+        "9223372036854775808 18446744073709551614 -9223372036854775809\n"
+    );
+}
+
+#[test]
+fn factorial() {
+    let (out, _) = run(r#"
+        fn fact(n) {
+            if n <= 1 {
+                return 1
+            }
+            return n * fact(n - 1)
+        }
+        print(fact(30))
+    "#);
+    assert_eq!(out, "265252859812191058636308480000000\n");
+}
+
+#[test]
+fn results_that_fit_become_integers_again() {
+    let (_, variables) = run(r#"
+        var big = 2 ** 100
+        var back = big - 2 ** 100 + 7
+        var quotient = big / 2 ** 99
+    "#);
+    assert_eq!(variables["back"], Value::Integer(Integer(7)));
+    assert_eq!(variables["quotient"].to_string(), "2.0");
+}
+
+#[test]
+fn conversions_and_comparisons() {
+    let (out, _) = run(r#"
+        var big = int("123456789012345678901234567890")
+        print(big + 1, str(big) == "123456789012345678901234567890", float(2 ** 70))
+        print(big > 2 ** 64, big == big + 0, 2 ** 64 < 1.5, abs(0 - big) == big)
+        print(f"{2 ** 64:x}", sum([9223372036854775807, 1]), f"{2 ** 64:s}")
+    "#);
+    assert_eq!(
+        out,
+        "123456789012345678901234567891 true 1.1805916207174113e+21\n\
+         true true false true\n\
+         10000000000000000 9223372036854775808 18446744073709551616\n"
+    );
+}
+
+#[test]
+fn big_integers_hash_consistently() {
+    let (out, _) = run(r#"
+        var d = {2 ** 70: "big"}
+        print(get(d, 2 ** 69 * 2), len({2 ** 70, 2 ** 70, 2 ** 70 - 0}))
+    "#);
+    assert_eq!(out, "big 1\n");
+}
+
+#[test]
+fn math_functions_accept_big_integers() {
+    let (out, _) = run(r#"
+        import math
+        var big = 2 ** 70
+        print(math.floor(big) == big, math.floor(10.0 ** 20), math.gcd(big, 2 ** 65 * 3))
+        print(math.lcm(2 ** 40, 3 ** 30), math.pow(2, 64), math.sqrt(big))
+    "#);
+    assert_eq!(
+        out,
+        "true 100000000000000000000 36893488147419103232\n\
+         226379693794030958489370624 18446744073709551616 34359738368.0\n"
+    );
+}
+
+#[test]
+fn powers_of_zero_and_one_take_any_exponent() {
+    let (out, _) = run(r#"
+        var huge = 2 ** 100
+        print(1 ** huge, 0 ** huge, (0 - 1) ** huge, (0 - 1) ** (huge + 1))
+    "#);
+    assert_eq!(out, "1 0 1 -1\n");
+}
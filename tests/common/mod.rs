@@ -0,0 +1,70 @@
+// Helpers shared by the integration tests; each test file uses only some of them
+#![allow(dead_code)]
+
+use pest::iterators::Pairs;
+use std::collections::HashMap;
+use std::io::Cursor;
+use tung_lang::interpreter::execute_program;
+use tung_lang::output::OutputBuffer;
+use tung_lang::parser::Rule;
+use tung_lang::value::{Integer, Value};
+use tung_lang::{Parser, StdLib, TungParser};
+
+/// What running a program left behind: its printed output, its variables and how it ended
+pub struct Run {
+    pub output: String,
+    pub error_output: String,
+    pub variables: HashMap<String, Value>,
+    pub result: miette::Result<()>,
+}
+
+/// The top-level statements of a program that must parse
+pub fn parse(source: &'static str) -> Pairs<'static, Rule> {
+    TungParser::parse(Rule::program, source)
+        .expect("program should parse")
+        .next()
+        .unwrap()
+        .into_inner()
+}
+
+/// Runs a program reading `input()` lines from `input` and capturing what it prints;
+/// `configure` adjusts the standard library first, e.g. to seed it or add a search path
+pub fn execute(source: &'static str, input: &str, configure: impl FnOnce(StdLib) -> StdLib) -> Run {
+    let parsed: Pairs<'static, Rule> = parse(source);
+    let output: OutputBuffer = OutputBuffer::new();
+    let error_output: OutputBuffer = OutputBuffer::new();
+    let stdlib: StdLib = configure(StdLib::with_io(
+        Box::new(Cursor::new(input.to_string())),
+        Box::new(output.clone()),
+        Box::new(error_output.clone()),
+    ));
+    let mut variables: HashMap<String, Value> = HashMap::new();
+    let result: miette::Result<()> = execute_program(parsed, &mut variables, &stdlib);
+    Run {
+        output: output.contents(),
+        error_output: error_output.contents(),
+        variables,
+        result,
+    }
+}
+
+/// Runs a program, returning what it printed
+pub fn run(source: &'static str) -> miette::Result<String> {
+    let run: Run = execute(source, "", |stdlib| stdlib);
+    run.result.map(|()| run.output)
+}
+
+/// Runs a program, returning its variables
+pub fn variables(source: &'static str) -> miette::Result<HashMap<String, Value>> {
+    let run: Run = execute(source, "", |stdlib| stdlib);
+    run.result.map(|()| run.variables)
+}
+
+/// Runs a program that must fail, returning the error message
+pub fn error_message(source: &'static str) -> String {
+    run(source).unwrap_err().to_string()
+}
+
+pub fn int(n: i64) -> Value {
+    Value::Integer(Integer(n))
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn equality_is_deep_and_structural() {
+    let out = run(r#"
+        print([1, [2, 3]] == [1, [2, 3]], [1, 2] == [2, 1], (1, "a") == (1, "a"))
+        print({"a": 1, "b": [2]} == {"b": [2], "a": 1}, {"a": 1} == {"a": 2})
+        print({1, 2} == {2, 1}, [1] == (1,), [] != [])
+    "#)
+    .unwrap();
+    assert_eq!(out, "true false true\ntrue false\ntrue false false\n");
+}
+
+#[test]
+fn equality_across_types_is_false_not_an_error() {
+    let out = run(r#"
+        fn f() {
+            return 1
+        }
+        struct Point { x, y }
+        print(1 == "1", "a" == ["a"], {} == [], 1 != "1")
+        print(f == f, Point(1, 2) == Point(1, 2), Point(1, 2) == Point(1, 3), Point == Point)
+        print(print("x") == print("y"), f == Point)
+    "#)
+    .unwrap();
+    assert_eq!(
+        out,
+        "false false false true\ntrue true false true\nx\ny\ntrue false\n"
+    );
+}
+
+#[test]
+fn numbers_compare_by_value() {
+    let out = run(r#"
+        print(1 == 1.0, [1, 2] == [1.0, 2.0], 2 < 2.5, 3 >= 3.0)
+        print(9007199254740993 > 9007199254740992.0, 9007199254740993 == 9007199254740992.0)
+        var nan = float("nan")
+        print(nan == nan, nan != nan, nan < 1, nan >= 1)
+        print(get({1: "a"}, 1.0) == "a", 1.0 in {1, 2}, len({1, 1.0, 2}))
+    "#)
+    .unwrap();
+    assert_eq!(
+        out,
+        "true true true true\ntrue false\nfalse true false false\ntrue true 2\n"
+    );
+}
+
+#[test]
+fn sequences_order_lexicographically() {
+    let out = run(r#"
+        print([1, 2] < [1, 3], [1, 2] < [1, 2, 0], [2] > [1, 9], [] < [0])
+        print((1, "b") > (1, "a"), (1, 2) <= (1, 2), ["a", [1]] < ["a", [2]])
+        print("apple" < "banana", "Z" < "a", "b" > "abc")
+    "#)
+    .unwrap();
+    assert_eq!(out, "true true true true\ntrue true true\ntrue true true\n");
+}
+
+#[test]
+fn ordering_incomparable_types_is_an_error() {
+    for source in [
+        "print(1 < \"1\")",
+        "print([1] < (1,))",
+        "print({} < {})",
+        "print([1, 2] < [1, \"a\"])",
+    ] {
+        let error = run(source).unwrap_err();
+        assert!(
+            error.to_string().starts_with("Error: Cannot compare "),
+            "{}: {}",
+            source,
+            error
+        );
+    }
+    let error = run("print(1 <= \"1\")").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Error: Cannot compare int and string with '<='."
+    );
+}
+
+#[test]
+fn sort_min_and_max_use_the_same_order() {
+    let out = run(r#"
+        print(sort([(2, "b"), (1, "z"), (2, "a")]))
+        print(sort([3, 1.5, 2]), min([3, 1.5, 2]), max("b", "c", "a"))
+        print(max([[1, 2], [1, 3], [0, 9]]))
+    "#)
+    .unwrap();
+    assert_eq!(
+        out,
+        "[(1, \"z\"), (2, \"a\"), (2, \"b\")]\n[1.5, 2, 3] 1.5 c\n[1, 3]\n"
+    );
+}
+
+#[test]
+fn chained_comparisons_evaluate_each_operand_once() {
+    let out = run(r#"
+        fn f(x) {
+            print("f", x)
+            return x
+        }
+        var x = 5
+        print(1 < 2 < 3, 3 > 2 > 2, 0 <= x < 10, 1 < x == 5 != 6)
+        print(f(1) < f(2) < f(3))
+        print(f(3) < f(2) < f(1))
+    "#)
+    .unwrap();
+    assert_eq!(
+        out,
+        "true false true true\nf 1\nf 2\nf 3\ntrue\nf 3\nf 2\nfalse\n"
+    );
+}
+
+#[test]
+fn chains_stop_before_a_comparison_that_would_fail() {
+    let out = run(r#"print(2 < 1 < "a")"#).unwrap();
+    assert_eq!(out, "false\n");
+    let error = run(r#"print(1 < 2 < "a")"#).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Error: Cannot compare int and string with '<'."
+    );
+}
+
+#[test]
+fn constants_and_identity() {
+    let out = run(r#"
+        fn nothing() {
+            var unused = 1
+        }
+        var nonempty = [true, false, none]
+        print(nonempty, none == none, nothing() is none, get({}, "k") is none)
+        print(1 is 1, 1 is 1.0, 1 is not 1.0, 0 is not none, "a" is "a", true is not false)
+    "#)
+    .unwrap();
+    assert_eq!(
+        out,
+        "[true, false, none] true true true\ntrue false true true true true\n"
+    );
+}
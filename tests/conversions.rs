@@ -0,0 +1,100 @@
+mod common;
+
+use common::run;
+
+/// The kind and message of the error a program stops with
+fn error(source: &'static str) -> (String, String) {
+    let error: miette::Report = run(source).unwrap_err();
+    let kind: String = error.code().unwrap().to_string();
+    (kind, error.to_string())
+}
+
+#[test]
+fn int_truncates_floats_towards_zero() {
+    let output: String = run("print(int(2.9), int(0 - 2.9), int(0.0), int(1000000.5))").unwrap();
+    assert_eq!(output, "2 -2 0 1000000\n");
+}
+
+#[test]
+fn int_of_a_float_without_an_int_value_is_an_error() {
+    assert_eq!(
+        error("import math\nprint(int(math.nan))"),
+        (
+            "ValueError".to_string(),
+            "Error: Cannot convert NaN to an integer in int(nan).".to_string()
+        )
+    );
+    assert_eq!(
+        error("import math\nprint(int(math.inf))"),
+        (
+            "OverflowError".to_string(),
+            "Error: Cannot convert infinity to an integer in int(inf).".to_string()
+        )
+    );
+}
+
+#[cfg(not(feature = "bigint"))]
+#[test]
+fn int_of_a_float_too_large_for_64_bits_is_an_overflow() {
+    assert_eq!(
+        error("print(int(10.0 ** 30))"),
+        (
+            "OverflowError".to_string(),
+            "Error: Integer overflow: int(1e+30) does not fit in 64 bits.".to_string()
+        )
+    );
+}
+
+#[cfg(not(feature = "bigint"))]
+#[test]
+fn round_of_a_float_too_large_for_64_bits_is_an_overflow() {
+    assert_eq!(
+        error("import math\nprint(math.round(10.0 ** 30))"),
+        (
+            "OverflowError".to_string(),
+            "Error: Integer overflow: round(1e+30) does not fit in 64 bits.".to_string()
+        )
+    );
+    assert_eq!(
+        run("print(round(2.5), round(0 - 2.4), round(2.345, 2))").unwrap(),
+        "3 -2 2.35\n"
+    );
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn int_of_a_large_float_is_exact_with_bigint() {
+    let output: String = run("print(int(10.0 ** 30))").unwrap();
+    assert_eq!(output, "1000000000000000019884624838656\n");
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn round_of_a_large_float_is_exact_with_bigint() {
+    let output: String = run("import math\nprint(math.round(10.0 ** 30))").unwrap();
+    assert_eq!(output, "1000000000000000019884624838656\n");
+}
+
+#[test]
+fn int_parses_strings_the_same_with_or_without_bigint() {
+    let output: String =
+        run(r#"print(int(" 42 "), int("+7"), int("\t-3\n"), int("007"))"#).unwrap();
+    assert_eq!(output, "42 7 -3 7\n");
+    for (source, literal) in [
+        (r#"int("1_000")"#, r#""1_000""#),
+        (r#"int("4 2")"#, r#""4 2""#),
+        (r#"int("  ")"#, r#""  ""#),
+        (r#"int("-")"#, r#""-""#),
+        (r#"int("+-1")"#, r#""+-1""#),
+        (r#"int("1.5")"#, r#""1.5""#),
+    ] {
+        assert_eq!(
+            error(source),
+            (
+                "ValueError".to_string(),
+                format!("Error: Invalid literal for int(): {literal}.")
+            ),
+            "{source}"
+        );
+    }
+}
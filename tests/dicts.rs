@@ -0,0 +1,58 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn keeps_insertion_order() {
+    let out = run(r#"
+        var d = {"zebra": 1, "apple": 2, "mango": 3}
+        d.update({"apple": 0, "kiwi": 4})
+        print(d)
+        print(keys(d), values(d))
+    "#)
+    .unwrap();
+    assert_eq!(
+        out,
+        "{\"zebra\": 1, \"apple\": 0, \"mango\": 3, \"kiwi\": 4}\n\
+         [\"zebra\", \"apple\", \"mango\", \"kiwi\"] [1, 0, 3, 4]\n"
+    );
+}
+
+#[test]
+fn non_string_and_computed_keys() {
+    let out = run(r#"
+        var n = 1
+        var d = {n: "one", n + 1: "two", 2.5: "float", 1 == 1: "bool", "s": "string"}
+        print(get(d, 2), get(d, 2.5), get(d, 1 == 1), get(d, "s"))
+        print(3 in d, "s" in d)
+    "#)
+    .unwrap();
+    assert_eq!(out, "two float bool string\nfalse true\n");
+}
+
+#[test]
+fn dict_builtins() {
+    let out = run(r#"
+        var d = {"a": 1, "b": 2}
+        print(items(d))
+        print(get(d, "missing", 0), d.get("a"))
+        print(remove(d, "a"), d)
+        print(d.remove("a"), d)
+        print(update({"x": 1}, {"y": 2}))
+    "#)
+    .unwrap();
+    assert_eq!(
+        out,
+        "[(\"a\", 1), (\"b\", 2)]\n\
+         0 1\n\
+         {\"b\": 2} {\"a\": 1, \"b\": 2}\n\
+         1 {\"b\": 2}\n\
+         {\"x\": 1, \"y\": 2}\n"
+    );
+}
+
+#[test]
+fn unhashable_key_is_an_error() {
+    let error = run(r#"var d = {[1, 2]: "list"}"#).unwrap_err();
+    assert!(error.to_string().contains("Unhashable type 'array'"));
+}
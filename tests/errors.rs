@@ -0,0 +1,191 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn catch_binds_message_kind_and_line() {
+    let output: String = run(r#"
+var x = 0
+try {
+    x = 1
+    var y = x / 0
+    x = 2
+} catch e {
+    print(e.kind, e.message, e.line, x)
+}
+    "#)
+    .unwrap();
+    assert_eq!(output, "ZeroDivisionError Division by zero. 5 1\n");
+}
+
+#[test]
+fn built_in_errors_have_kinds() {
+    let output: String = run(r#"
+fn kind_of(code) {
+    try {
+        match code {
+            0 => {
+                print(missing)
+            }
+            1 => {
+                var x = 1 - "a"
+            }
+            2 => {
+                var x = 1 // 0
+            }
+            3 => {
+                var x = int("abc")
+            }
+            4 => {
+                var (a, b) = [1, 2, 3]
+            }
+            5 => {
+                var x = [1].nope()
+            }
+        }
+    } catch e {
+        return e.kind
+    }
+    return "none"
+}
+for code in range(7) {
+    print(kind_of(code))
+}
+    "#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "NameError\nTypeError\nZeroDivisionError\nValueError\nValueError\nAttributeError\nnone\n"
+    );
+}
+
+#[test]
+fn catch_clauses_filter_by_kind() {
+    let output: String = run(r#"
+fn attempt(value) {
+    try {
+        return 10 / value
+    } catch ZeroDivisionError {
+        return "divided by zero"
+    } catch NameError | TypeError e {
+        return e.kind
+    }
+}
+print(attempt(2), attempt(0), attempt("x"))
+try {
+    try {
+        print(nope)
+    } catch ZeroDivisionError {
+        print("wrong clause")
+    }
+} catch Error e {
+    print("outer", e.kind)
+}
+    "#)
+    .unwrap();
+    assert_eq!(output, "5.0 divided by zero TypeError\nouter NameError\n");
+}
+
+#[test]
+fn uncaught_errors_keep_their_message() {
+    let error = run(r#"
+try {
+    var x = 1 / 0
+} catch NameError {
+    print("not this one")
+}
+    "#)
+    .unwrap_err();
+    assert_eq!(error.to_string(), "Error: Division by zero.");
+    assert_eq!(error.code().unwrap().to_string(), "ZeroDivisionError");
+}
+
+#[test]
+fn raise_and_throw() {
+    let output: String = run(r#"
+fn check(age) {
+    if age < 0 {
+        raise Error("age is negative", "ValueError")
+    }
+    if age > 150 {
+        throw "age is too large"
+    }
+    return age
+}
+for age in [30, 0 - 1, 200] {
+    try {
+        print(check(age))
+    } catch ValueError e {
+        print("value:", e.message)
+    } catch e {
+        print(e.kind + ":", e.message)
+    }
+}
+try {
+    raise 42
+} catch e {
+    print(e.kind, e.message)
+}
+try {
+    try {
+        raise "first"
+    } catch e {
+        raise Error("second: " + e.message, kind = e.kind)
+    }
+} catch e {
+    print(e.message)
+}
+    "#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "30\nvalue: age is negative\nError: age is too large\n\
+         TypeError Can only raise a string or an Error, not int.\nsecond: first\n"
+    );
+    assert_eq!(
+        run(r#"raise "boom""#).unwrap_err().to_string(),
+        "Error: boom"
+    );
+}
+
+#[test]
+fn finally_always_runs() {
+    let output: String = run(r#"
+fn cleanup(fail) {
+    try {
+        if fail {
+            raise "failed"
+        }
+        return "done"
+    } catch e {
+        return "caught " + e.message
+    } finally {
+        print("cleanup", fail)
+    }
+}
+print(cleanup(false))
+print(cleanup(true))
+fn overridden() {
+    try {
+        return 1
+    } finally {
+        return 2
+    }
+}
+print(overridden())
+    "#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "cleanup false\ndone\ncleanup true\ncaught failed\n2\n"
+    );
+    let error = run(r#"
+try {
+    raise "inside"
+} finally {
+    print("still runs")
+}
+    "#)
+    .unwrap_err();
+    assert_eq!(error.to_string(), "Error: inside");
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn floats_without_a_type_look_like_str() {
+    let output: String = run(r#"
+var x = 1.0
+print(f"[{x:>6}]", f"[{x:<6}]", f"[{x}]", f"[{2.5:^7}]", f"[{0.1 + 0.2:6}]")
+print(f"[{10000000000000000.0:>8}]", f"[{7:>4}]", f"[{7.0:+}]")
+"#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "[   1.0] [1.0   ] [1.0] [  2.5  ] [0.30000000000000004]\n\
+         [   1e+16] [   7] [+7.0]\n"
+    );
+}
+
+#[test]
+fn exponent_notation() {
+    let output: String = run(r#"
+print(f"{2.5:e}", f"{2.5:E}", f"{12345.678:.2e}", f"{0.00012:e}", f"{0 - 2.5:e}")
+print(f"{7:e}", f"{0.0:.1e}", f"[{1234.5:>12.3e}]", f"{0.000025:.0e}")
+"#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "2.500000e+00 2.500000E+00 1.23e+04 1.200000e-04 -2.500000e+00\n\
+         7.000000e+00 0.0e+00 [   1.234e+03] 3e-05\n"
+    );
+}
+
+#[test]
+fn precision_padding_and_integer_types() {
+    let output: String = run(r#"
+var price = 3.14159
+print(f"{price:.2f}", f"{price:f}", f"{price:08.3f}", f"{0.25:%}", f"{0.25:.0%}")
+print(f"{255:x}", f"{255:X}", f"{8:o}", f"{5:b}", f"{42:05d}", f"{42:+d}", f"{0 - 42:*>6}")
+print(f"{"abc":.2}", f"[{"ab":^6}]", f"[{"ab":-<5}]")
+"#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "3.14 3.141590 0003.142 25.000000% 25%\n\
+         ff FF 10 101 00042 +42 ***-42\n\
+         ab [  ab  ] [ab---]\n"
+    );
+}
+
+#[test]
+fn s_shows_numbers_as_their_str_text() {
+    let output: String = run(r#"
+print(f"{42:s}", f"[{42:8s}]", f"[{42:>6s}]", f"{1.0:s}", f"{0 - 7:s}", f"{12345:.3s}")
+"#)
+    .unwrap();
+    assert_eq!(output, "42 [42      ] [    42] 1.0 -7 123\n");
+}
+
+#[test]
+fn invalid_specs_are_errors() {
+    assert!(run(r#"print(f"{1.5:d}")"#).is_err());
+    assert!(run(r#"print(f"{"a":x}")"#).is_err());
+    assert!(run(r#"print(f"{1:q}")"#).is_err());
+}
+
+#[test]
+fn str_and_repr() {
+    let output: String = run(r#"
+print(str(1.0), str(0.1 + 0.2), str(10000000000000000.0), str(0.00001), str(2.5), str(0.0))
+print(str("a\"b"), repr("a\"b\n"), repr(1.5), repr([1.0, "x"]), str([1.0, "x"]))
+print(repr((1,)), repr(set()), repr({"k": (2.0, none)}), str(none), repr(true))
+"#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "1.0 0.30000000000000004 1e+16 1e-05 2.5 0.0\n\
+         a\"b \"a\\\"b\\n\" 1.5 [1.0, \"x\"] [1.0, \"x\"]\n\
+         (1,) set() {\"k\": (2.0, none)} none true\n"
+    );
+}
+
+#[test]
+fn fields_and_str_share_one_float_formatter() {
+    let output: String = run(r#"
+var values = [1.0, 0.1 + 0.2, 10000000000000000.0, 0.00001, 0 - 2.5]
+for v in values {
+    print(f"{v}" == str(v), f"{v:}" == str(v))
+}
+"#)
+    .unwrap();
+    assert_eq!(output, "true true\n".repeat(5));
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use common::{int, run, variables};
+use tung_lang::value::{StringValue, Value};
+
+/// The message of the error a call fails with and the source text its label points at
+fn call_error(source: &'static str) -> (String, &'static str) {
+    let error: miette::Report = run(source).unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "TypeError");
+    let label = error.labels().unwrap().next().unwrap();
+    (
+        error.to_string(),
+        &source[label.offset()..label.offset() + label.len()],
+    )
+}
+
+#[test]
+fn zero_parameters() {
+    let variables = variables(
+        r#"
+        fn answer() {
+            return 42
+        }
+        var result = answer()
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["result"], int(42));
+}
+
+#[test]
+fn one_parameter() {
+    let variables = variables(
+        r#"
+        fn double(x) {
+            return x * 2
+        }
+        var result = double(21)
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["result"], int(42));
+}
+
+#[test]
+fn two_parameters() {
+    let variables = variables(
+        r#"
+        fn add(a, b) {
+            return a + b
+        }
+        var result = add(40, 2)
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["result"], int(42));
+}
+
+#[test]
+fn many_parameters_keep_their_order() {
+    let variables = variables(
+        r#"
+        fn join(a, b, c, d) {
+            return a + b + c + d
+        }
+        var result = join("w", "x", "y", "z")
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        variables["result"],
+        Value::String(StringValue("wxyz".to_string()))
+    );
+}
+
+#[test]
+fn default_keyword_and_variadic_parameters() {
+    let variables = variables(
+        r#"
+        fn greet(name, greeting = "hi") {
+            return greeting + " " + name
+        }
+        fn count(first, *rest) {
+            return len(rest)
+        }
+        var plain = greet("bob")
+        var named = greet(greeting = "yo", name = "bob")
+        var extra = count(1, 2, 3)
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        variables["plain"],
+        Value::String(StringValue("hi bob".to_string()))
+    );
+    assert_eq!(
+        variables["named"],
+        Value::String(StringValue("yo bob".to_string()))
+    );
+    assert_eq!(variables["extra"], int(2));
+}
+
+#[test]
+fn recursive_function() {
+    let variables = variables(
+        r#"
+        fn fact(n) {
+            if n <= 1 {
+                return 1
+            }
+            return n * fact(n - 1)
+        }
+        var result = fact(5)
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["result"], int(120));
+}
+
+#[test]
+fn recursive_function_called_through_an_alias_or_a_parameter() {
+    let variables = variables(
+        r#"
+        fn fact(n) {
+            if n <= 1 {
+                return 1
+            }
+            return n * fact(n - 1)
+        }
+        fn apply(f, x) {
+            return f(x)
+        }
+        var g = fact
+        var aliased = g(5)
+        var passed = apply(fact, 4)
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["aliased"], int(120));
+    assert_eq!(variables["passed"], int(24));
+}
+
+#[test]
+fn arity_mismatch_is_an_error() {
+    let too_few = variables(
+        r#"
+        fn add(a, b) {
+            return a + b
+        }
+        var result = add(1)
+    "#,
+    );
+    assert!(too_few.unwrap_err().to_string().contains("missing"));
+
+    let too_many = variables(
+        r#"
+        fn add(a, b) {
+            return a + b
+        }
+        var result = add(1, 2, 3)
+    "#,
+    );
+    assert!(too_many.unwrap_err().to_string().contains("takes 2"));
+}
+
+#[test]
+fn defaults_are_evaluated_once_when_the_function_is_defined() {
+    let output: String = run(r#"
+var base = 10
+fn add(x, y = base * 2) {
+    return x + y
+}
+base = 0
+print(add(1), add(1, 2), add(y = 5, x = 1))
+"#)
+    .unwrap();
+    assert_eq!(output, "21 3 6\n");
+}
+
+#[test]
+fn rest_collects_extra_positional_arguments() {
+    let output: String = run(r#"
+fn log(level, *messages, sep = " ") {
+    return level + ": " + join(sep, messages)
+}
+print(log("info"), log("warn", "a", "b"), log("err", "x", "y", sep = "/"))
+"#)
+    .unwrap();
+    assert_eq!(output, "info:  warn: a b err: x/y\n");
+}
+
+#[test]
+fn arity_errors_point_at_the_call() {
+    assert_eq!(
+        call_error("fn f(a, b = 1) {\n    return a\n}\nvar x = f()\n"),
+        (
+            "Error: Function 'f' is missing required argument 'a'.".to_string(),
+            "f()"
+        )
+    );
+    assert_eq!(
+        call_error("fn f(a) {\n}\nprint(1, f(1, 2))\n"),
+        (
+            "Error: Function 'f' takes 1 positional argument but 2 were given.".to_string(),
+            "f(1, 2)"
+        )
+    );
+    assert_eq!(
+        call_error("fn f(a) {\n}\nf(1, a = 2)\n"),
+        (
+            "Error: Function 'f' got multiple values for argument 'a'.".to_string(),
+            "f(1, a = 2)"
+        )
+    );
+    assert_eq!(
+        call_error("fn f(a, *rest) {\n}\nf(1, rest = 2)\n"),
+        (
+            "Error: Function 'f' got an unexpected keyword argument 'rest'.".to_string(),
+            "f(1, rest = 2)"
+        )
+    );
+}
+
+#[test]
+fn invalid_parameter_lists_are_errors() {
+    for source in [
+        "fn f(a, a) {\n}\n",
+        "fn f(a = 1, b) {\n}\n",
+        "fn f(*a = []) {\n}\n",
+        "fn f(*a, *b) {\n}\n",
+    ] {
+        assert!(run(source).is_err(), "{}", source);
+    }
+}
@@ -0,0 +1,218 @@
+//! Golden-file tests: every `tests/fixtures/*.tung` program runs through the library, and what
+//! it writes to stdout and stderr, plus its exit code, must match `<name>.expected`. A
+//! `<name>.stdin` file, if present, is fed to `input()`. Run with `UPDATE_EXPECTED=1` to rewrite
+//! the `.expected` files after an intended change, then review the diff.
+mod common;
+
+use common::{execute, Run};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tung_lang::eval::errors::from_report;
+use tung_lang::parser::Rule;
+use tung_lang::preprocess::preprocess_code;
+use tung_lang::value::Value;
+use tung_lang::warnings::check_program;
+use tung_lang::{Parser, StdLib, TungParser};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+const GRAMMAR: &str = include_str!("../src/tung.pest");
+
+/// Rules matched inside an atomic rule, which leaves them out of the parse tree; a fixture
+/// covers one of these when some part of its source parses as it
+const INSIDE_ATOMIC_RULES: &[(&str, Rule)] = &[
+    ("raw_string", Rule::raw_string),
+    ("triple_quoted_string", Rule::triple_quoted_string),
+    ("double_quoted_string", Rule::double_quoted_string),
+    ("single_quoted_string", Rule::single_quoted_string),
+];
+
+/// What running a program did, as the command line would show it
+struct Outcome {
+    stdout: String,
+    stderr: String,
+    exit_code: i32,
+}
+
+impl Outcome {
+    /// The `.expected` file contents for this outcome; empty stderr and exit code 0 are left out
+    fn render(&self) -> String {
+        let mut text: String = String::new();
+        let mut section = |header: &str, content: &str| {
+            text.push_str(&format!("--- {}\n{}", header, content));
+            if !content.is_empty() && !content.ends_with('\n') {
+                text.push_str("\n\\ no newline at end\n");
+            }
+        };
+        section("stdout", &self.stdout);
+        if !self.stderr.is_empty() {
+            section("stderr", &self.stderr);
+        }
+        if self.exit_code != 0 {
+            section("exit code", &format!("{}\n", self.exit_code));
+        }
+        text
+    }
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(FIXTURES)
+        .expect("fixtures directory should exist")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "tung")
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// The program text of a fixture, with keyword aliases replaced as the command line does
+fn load(path: &Path) -> &'static str {
+    let source: String = fs::read_to_string(path).unwrap();
+    Box::leak(preprocess_code(&source).into_boxed_str())
+}
+
+/// Runs a program like `tung-lang -f` does: warnings first, then the program, and an uncaught
+/// error ends it with exit code 1
+fn run(path: &Path, source: &'static str, stdin: String) -> Outcome {
+    let mut errors: String = String::new();
+    match TungParser::parse(Rule::program, source) {
+        Ok(mut pairs) => {
+            for warning in check_program(pairs.next().unwrap().into_inner()) {
+                errors.push_str(&format!("{}\n", warning));
+            }
+            let run: Run = execute(source, &stdin, |stdlib: StdLib| {
+                stdlib.modules().register_entry(source, path);
+                stdlib
+            });
+            if let Err(error) = &run.result {
+                errors.push_str(&describe_error(error, source));
+            }
+            Outcome {
+                exit_code: if run.result.is_ok() { 0 } else { 1 },
+                stdout: run.output,
+                stderr: run.error_output + &errors,
+            }
+        }
+        Err(error) => Outcome {
+            stdout: String::new(),
+            stderr: format!("Error parsing program: {}\n", error),
+            exit_code: 1,
+        },
+    }
+}
+
+/// An uncaught error as one line: its kind, line and message
+fn describe_error(error: &miette::Report, source: &str) -> String {
+    let Value::Struct(error) = from_report(error, source) else {
+        unreachable!("errors are structs");
+    };
+    let field = |name: &str| error.get(name).map(|value| value.to_string()).unwrap();
+    format!(
+        "{} on line {}: {}\n",
+        field("kind"),
+        field("line"),
+        field("message")
+    )
+}
+
+#[test]
+fn fixtures_match_expected_output() {
+    let update: bool = std::env::var_os("UPDATE_EXPECTED").is_some();
+    let mut mismatches: Vec<String> = Vec::new();
+    for path in fixtures() {
+        let stdin: String = fs::read_to_string(path.with_extension("stdin")).unwrap_or_default();
+        let actual: String = run(&path, load(&path), stdin).render();
+        let expected_path: PathBuf = path.with_extension("expected");
+        if update {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+        let expected: String = fs::read_to_string(&expected_path).unwrap_or_default();
+        if actual != expected {
+            mismatches.push(format!(
+                "{}\n=== expected ===\n{}=== actual ===\n{}",
+                path.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "{} fixture(s) did not match; rerun with UPDATE_EXPECTED=1 to accept the new output\n\n{}",
+        mismatches.len(),
+        mismatches.join("\n")
+    );
+}
+
+/// Every rule a fixture's parse tree contains, by name
+fn collect_rules(pair: pest::iterators::Pair<Rule>, rules: &mut BTreeSet<String>) {
+    rules.insert(format!("{:?}", pair.as_rule()));
+    for inner in pair.into_inner() {
+        collect_rules(inner, rules);
+    }
+}
+
+#[test]
+fn fixtures_cover_every_grammar_rule() {
+    let sources: Vec<&'static str> = fixtures().iter().map(|path| load(path)).collect();
+    let mut covered: BTreeSet<String> = BTreeSet::new();
+    for source in &sources {
+        for pair in TungParser::parse(Rule::program, source).expect("fixture should parse") {
+            collect_rules(pair, &mut covered);
+        }
+    }
+    for (name, rule) in INSIDE_ATOMIC_RULES {
+        let parses_somewhere = |source: &&str| {
+            source
+                .char_indices()
+                .any(|(start, _)| TungParser::parse(*rule, &source[start..]).is_ok())
+        };
+        if sources.iter().any(parses_somewhere) {
+            covered.insert(name.to_string());
+        }
+    }
+    // Silent rules, written `name = _{ ... }`, never show up in a parse tree
+    let missing: Vec<&str> = GRAMMAR
+        .lines()
+        .filter_map(|line| line.split_once(" = "))
+        .filter(|(name, body)| {
+            !name.contains(char::is_whitespace) && !name.starts_with("//") && !body.starts_with('_')
+        })
+        .map(|(name, _)| name)
+        .filter(|name| !covered.contains(*name))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "no fixture uses these grammar rules: {:?}",
+        missing
+    );
+}
+
+/// Whether a program calls a function by name, as in `len(xs)`; method calls like `xs.len()`
+/// and longer names like `strlen(` do not count
+fn calls(source: &str, name: &str) -> bool {
+    source
+        .match_indices(&format!("{}(", name))
+        .any(|(start, _)| {
+            !source[..start].ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        })
+}
+
+#[test]
+fn fixtures_cover_every_builtin() {
+    let sources: Vec<&'static str> = fixtures().iter().map(|path| load(path)).collect();
+    let missing: Vec<String> = StdLib::new()
+        .function_names()
+        .into_iter()
+        .filter(|name| !sources.iter().any(|source| calls(source, name)))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "no fixture calls these built-ins: {:?}",
+        missing
+    );
+}
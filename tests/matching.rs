@@ -0,0 +1,163 @@
+mod common;
+
+use common::{parse, run};
+use tung_lang::warnings::check_program;
+
+/// The warnings a program gets before it runs
+fn warnings(source: &'static str) -> Vec<String> {
+    check_program(parse(source))
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect()
+}
+
+const DESCRIBE: &str = r#"
+    fn describe(x) {
+        match x {
+            0 => {
+                return "zero"
+            }
+            -1 | -2 => {
+                return "small negative"
+            }
+            1..10 => {
+                return "digit"
+            }
+            10..=100 => {
+                return "medium"
+            }
+            "hi" | none => {
+                return "literal"
+            }
+            [] => {
+                return "empty"
+            }
+            [a, 0] => {
+                return f"{a} then zero"
+            }
+            [first, *middle, last] => {
+                return f"{first} {middle} {last}"
+            }
+            _ => {
+                return "other"
+            }
+        }
+    }
+"#;
+
+/// Prints `describe(v)` for each value in an array literal
+fn describe_each(values: &str) -> String {
+    let source: String = format!(
+        "{}\nfor v in {} {{\n print(describe(v))\n}}",
+        DESCRIBE, values
+    );
+    run(Box::leak(source.into_boxed_str())).unwrap()
+}
+
+#[test]
+fn literal_range_and_wildcard_patterns() {
+    let out = describe_each(r#"[0, 0 - 2, 9, 9.5, 10, 100, 101, "hi", none, "other"]"#);
+    assert_eq!(
+        out,
+        "zero\nsmall negative\ndigit\ndigit\nmedium\nmedium\nother\nliteral\nliteral\nother\n"
+    );
+}
+
+#[test]
+fn array_patterns_destructure() {
+    let out = describe_each("[[], [7, 0], (7, 0), [1, 2, 3, 4], [1, 2], [1]]");
+    assert_eq!(
+        out,
+        "empty\n7 then zero\n7 then zero\n1 [2, 3] 4\n1 [] 2\nother\n"
+    );
+}
+
+#[test]
+fn captures_are_local_to_their_arm() {
+    let out = run(r#"
+        var x = "outer"
+        var seen = none
+        match [1, [2, 3]] {
+            [x, [_, y]] => {
+                seen = (x, y)
+            }
+        }
+        print(x, seen)
+        match 5 {
+            0 => {
+                print("never")
+            }
+        }
+        print("no arm matched")
+    "#)
+    .unwrap();
+    assert_eq!(out, "outer (1, 3)\nno arm matched\n");
+}
+
+#[test]
+fn conditional_expressions_evaluate_one_side() {
+    let out = run(r#"
+        fn loud(x) {
+            print("evaluated", x)
+            return x
+        }
+        var n = 5
+        print("big" if n > 3 else "small", "big" if n > 30 else "small")
+        print(loud(1) if n == 5 else loud(2))
+        print(1 if false else 2 if false else 3)
+    "#)
+    .unwrap();
+    assert_eq!(out, "big small\nevaluated 1\n1\n3\n");
+}
+
+#[test]
+fn non_exhaustive_bool_matches_warn() {
+    assert_eq!(
+        warnings(
+            r#"
+            match 1 < 2 {
+                true => {
+                    print("yes")
+                }
+            }
+            fn f(flag) {
+                match flag {
+                    false => {
+                        print("no")
+                    }
+                }
+            }
+        "#
+        ),
+        vec![
+            "Warning: This match on a bool is not exhaustive.",
+            "Warning: This match on a bool is not exhaustive."
+        ]
+    );
+    assert!(warnings(
+        r#"
+        match 1 < 2 {
+            true => {
+                print("yes")
+            }
+            false => {
+                print("no")
+            }
+        }
+        match 1 < 2 {
+            true => {
+                print("yes")
+            }
+            _ => {
+                print("no")
+            }
+        }
+        match 3 {
+            1 => {
+                print("one")
+            }
+        }
+    "#
+    )
+    .is_empty());
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use common::run;
+
+/// The kind and message of the error a program stops with
+fn error(source: &'static str) -> (String, String) {
+    let error: miette::Report = run(source).unwrap_err();
+    let kind: String = error.code().unwrap().to_string();
+    (kind, error.to_string())
+}
+
+#[test]
+fn methods_and_functions_share_one_implementation() {
+    let output: String = run(r#"
+var xs = [3, 1, 2]
+xs.append(4)
+var popped = xs.pop(0)
+xs.sort()
+print(xs, popped, append(xs, 5), xs, pop(xs), sort([2, 1]))
+var d = {"a": 1}
+d.update({"b": 2})
+print(d.remove("a"), d, remove(d, "b"), d.get("z", 0), get(d, "b"))
+var s = {1}
+s.add(2)
+print(s, add(s, 3), s)
+print("a,b".split(","), split("a b"), ", ".join(["x", "y"]), "hello".count("l"))
+"#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "[1, 2, 4] 3 [1, 2, 4, 5] [1, 2, 4] 4 [1, 2]\n\
+         1 {\"b\": 2} {} 0 2\n\
+         {1, 2} {1, 2, 3} {1, 2}\n\
+         [\"a\", \"b\"] [\"a\", \"b\"] x, y 2\n"
+    );
+}
+
+#[test]
+fn wrong_argument_counts_are_type_errors() {
+    assert_eq!(
+        error("var xs = []\nxs.append()"),
+        (
+            "TypeError".to_string(),
+            "Error: append() takes 1 argument but 0 were given.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(\"a b\".split(\" \", 1, 2))"),
+        (
+            "TypeError".to_string(),
+            "Error: split() takes 0 to 2 arguments but 3 were given.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(append([1]))"),
+        (
+            "TypeError".to_string(),
+            "Error: append() takes 2 arguments but 1 was given.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(upper())"),
+        (
+            "TypeError".to_string(),
+            "Error: upper() takes at least 1 argument but 0 were given.".to_string()
+        )
+    );
+}
+
+#[test]
+fn wrong_argument_types_are_type_errors() {
+    assert_eq!(
+        error("print(\", \".join(5))"),
+        (
+            "TypeError".to_string(),
+            "Error: join() needs an array, not int.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(upper(5))"),
+        (
+            "TypeError".to_string(),
+            "Error: upper() needs a string, not int.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(index(5, 1))"),
+        (
+            "TypeError".to_string(),
+            "Error: index() needs a string or an array, not int.".to_string()
+        )
+    );
+    assert_eq!(
+        error("var s = {1}\ns.add([2])"),
+        (
+            "TypeError".to_string(),
+            "Error: add() needs a hashable value, not array.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(\"abc\".upper(case = \"title\"))").0,
+        "TypeError"
+    );
+}
+
+#[test]
+fn bad_values_are_value_and_index_errors() {
+    assert_eq!(
+        error("var s = \"a,b\"\nprint(split(s, \"\"))"),
+        (
+            "ValueError".to_string(),
+            "Error: split() cannot split on an empty separator.".to_string()
+        )
+    );
+    assert_eq!(
+        error("var xs = []\nxs.pop()"),
+        (
+            "IndexError".to_string(),
+            "Error: Cannot pop from an empty array.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(pop([1, 2], 5))"),
+        (
+            "IndexError".to_string(),
+            "Error: Pop index 5 is out of range for an array of 2.".to_string()
+        )
+    );
+}
+
+#[test]
+fn errors_from_methods_can_be_caught() {
+    let output: String = run(r#"
+try {
+    "".split("")
+} catch ValueError e {
+    print(e.kind, e.line)
+}
+"#)
+    .unwrap();
+    assert_eq!(output, "ValueError 3\n");
+}
+
+#[test]
+fn format_fills_fields_in_order_by_number_or_with_a_spec() {
+    let output: String = run(r#"
+var name = "Ada"
+print("{} is {}".format(name, 36), "{1}, {0}!".format("world", "Hello"))
+print("[{0:>6.2f}] [{0:*^9}]".format(3.14159), "{{{}}}".format([1]), format("{}-{}", 1, 2.0))
+"#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "Ada is 36 Hello, world!\n[  3.14] [*3.14159*] {[1]} 1-2.0\n"
+    );
+}
+
+#[test]
+fn bad_format_templates_are_errors() {
+    assert_eq!(
+        error("print(\"{} {}\".format(1))"),
+        (
+            "IndexError".to_string(),
+            "Error: format() has no argument 1 for '{}'.".to_string()
+        )
+    );
+    assert_eq!(
+        error("print(\"{} {0}\".format(1))"),
+        (
+            "ValueError".to_string(),
+            "Error: format() cannot mix '{}' with numbered fields such as '{0}'.".to_string()
+        )
+    );
+    assert_eq!(error("print(\"{\".format(1))").0, "ValueError");
+    assert_eq!(error("print(\"}\".format(1))").0, "ValueError");
+    assert_eq!(error("print(\"{x}\".format(1))").0, "ValueError");
+    assert_eq!(
+        error("print(\"{:d}\".format(1.5))"),
+        (
+            "ValueError".to_string(),
+            "Error: Invalid format spec: cannot format float 1.5 as an integer.".to_string()
+        )
+    );
+}
+
+#[test]
+fn user_functions_shadow_built_in_functions_but_not_methods() {
+    let output: String = run(r#"
+import string
+fn split(s) {
+    return "mine: " + s
+}
+fn format(template, value) {
+    return template + "!"
+}
+print(split("a b"), "a b".split(), string.split("a b"))
+print(format("{}", 1), "{}".format(1))
+"#)
+    .unwrap();
+    assert_eq!(output, "mine: a b [\"a\", \"b\"] [\"a\", \"b\"]\n{}! 1\n");
+}
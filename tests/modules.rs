@@ -0,0 +1,376 @@
+mod common;
+
+use common::{execute, Run};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tung_lang::eval::errors::from_report;
+use tung_lang::modules::Modules;
+use tung_lang::value::Value;
+use tung_lang::StdLib;
+
+/// A fresh directory holding the given files, named after the test using it
+fn project(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let directory: PathBuf = std::env::temp_dir().join(format!("tung-modules-{}", test));
+    let _ = fs::remove_dir_all(&directory);
+    for (name, contents) in files {
+        let path: PathBuf = directory.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    directory
+}
+
+/// Runs `main.tung` from a project with the given search path
+fn execute_project(directory: &Path, search_path: Vec<PathBuf>) -> Run {
+    let path: PathBuf = directory.join("main.tung");
+    let source: &'static str = Box::leak(fs::read_to_string(&path).unwrap().into_boxed_str());
+    execute(source, "", |stdlib: StdLib| {
+        let stdlib: StdLib = stdlib.with_modules(Modules::new(search_path));
+        stdlib.modules().register_entry(source, &path);
+        stdlib
+    })
+}
+
+/// Runs `main.tung` from a project with the given search path, returning what it printed
+fn run(directory: &Path, search_path: Vec<PathBuf>) -> miette::Result<String> {
+    let run: Run = execute_project(directory, search_path);
+    run.result.map(|()| run.output)
+}
+
+#[test]
+fn imports_by_name_by_path_and_by_member() {
+    let directory: PathBuf = project(
+        "forms",
+        &[
+            (
+                "main.tung",
+                r#"
+import utils
+import "lib/shapes.tung"
+from utils import double, greeting
+print(utils.double(4), double(5), greeting, utils.greeting)
+print(shapes.area(2, 3), utils)
+"#,
+            ),
+            (
+                "utils.tung",
+                r#"
+var greeting = "hi"
+fn double(x) {
+    return x * 2
+}
+"#,
+            ),
+            (
+                "lib/shapes.tung",
+                r#"
+fn area(w, h) {
+    return w * h
+}
+"#,
+            ),
+        ],
+    );
+    assert_eq!(
+        run(&directory, Vec::new()).unwrap(),
+        "8 10 hi hi\n6 <module utils>\n"
+    );
+}
+
+#[test]
+fn runs_each_module_once() {
+    let directory: PathBuf = project(
+        "once",
+        &[
+            (
+                "main.tung",
+                r#"
+import counter
+import counter
+from counter import count
+import "helper.tung"
+print(count, helper.total)
+"#,
+            ),
+            (
+                "counter.tung",
+                "print(\"loading counter\")\nvar count = 1\n",
+            ),
+            (
+                "helper.tung",
+                "import counter\nvar total = counter.count + 1\n",
+            ),
+        ],
+    );
+    assert_eq!(
+        run(&directory, Vec::new()).unwrap(),
+        "loading counter\n1 2\n"
+    );
+}
+
+#[test]
+fn resolves_imports_next_to_the_importing_file_then_the_search_path() {
+    let directory: PathBuf = project(
+        "search",
+        &[
+            (
+                "main.tung",
+                "import \"lib/outer.tung\"\nprint(outer.value)\n",
+            ),
+            ("lib/outer.tung", "import inner\nvar value = inner.value\n"),
+            ("lib/inner.tung", "var value = \"beside outer\"\n"),
+            ("inner.tung", "var value = \"beside main\"\n"),
+            (
+                "vendor/extra.tung",
+                "var value = \"from the search path\"\n",
+            ),
+        ],
+    );
+    assert_eq!(run(&directory, Vec::new()).unwrap(), "beside outer\n");
+
+    fs::write(
+        directory.join("main.tung"),
+        "import extra\nprint(extra.value)\n",
+    )
+    .unwrap();
+    let error: miette::Report = run(&directory, Vec::new()).unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "ImportError");
+    assert!(error
+        .to_string()
+        .starts_with("Error: No module 'extra.tung' in "));
+    assert_eq!(
+        run(&directory, vec![directory.join("vendor")]).unwrap(),
+        "from the search path\n"
+    );
+}
+
+#[test]
+fn detects_circular_imports() {
+    let directory: PathBuf = project(
+        "circular",
+        &[
+            ("main.tung", "import a\n"),
+            ("a.tung", "import b\n"),
+            ("b.tung", "import a\n"),
+        ],
+    );
+    let error: miette::Report = run(&directory, Vec::new()).unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "ImportError");
+    assert_eq!(
+        error.to_string(),
+        "Error: Circular import: a.tung -> b.tung -> a.tung."
+    );
+}
+
+#[test]
+fn detects_circular_imports_through_the_entry_file() {
+    let directory: PathBuf = project(
+        "circular-entry",
+        &[
+            ("main.tung", "print(\"top of main\")\nimport b\n"),
+            ("b.tung", "import main\n"),
+        ],
+    );
+    let run: Run = execute_project(&directory, Vec::new());
+    let error: miette::Report = run.result.unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "ImportError");
+    assert_eq!(
+        error.to_string(),
+        "Error: Circular import: main.tung -> b.tung -> main.tung."
+    );
+    // The entry file ran once; it was not run again as a module
+    assert_eq!(run.output, "top of main\n");
+}
+
+#[test]
+fn missing_names_are_import_and_attribute_errors() {
+    let directory: PathBuf = project(
+        "missing",
+        &[
+            (
+                "main.tung",
+                r#"
+import utils
+try {
+    from utils import nothing
+} catch ImportError e {
+    print(e.message)
+}
+try {
+    utils.nothing()
+} catch AttributeError e {
+    print(e.message)
+}
+try {
+    print(utils.nothing)
+} catch AttributeError e {
+    print(e.message)
+}
+"#,
+            ),
+            ("utils.tung", "var something = 1\n"),
+        ],
+    );
+    assert_eq!(
+        run(&directory, Vec::new()).unwrap(),
+        "Module 'utils' has no name 'nothing'.\n\
+         Module 'utils' has no function 'nothing'.\n\
+         Module 'utils' has no name 'nothing'.\n"
+    );
+}
+
+#[test]
+fn errors_in_a_module_name_its_file_and_line() {
+    let directory: PathBuf = project(
+        "diagnostics",
+        &[
+            (
+                "main.tung",
+                "import broken\nprint(\"first\")\nprint(broken.divide(1))\n",
+            ),
+            (
+                "broken.tung",
+                "# A module with a bug\nfn divide(x) {\n    return x / 0\n}\n",
+            ),
+        ],
+    );
+    let error: miette::Report = run(&directory, Vec::new()).unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "ZeroDivisionError");
+    let rendered: String = format!("{:?}", error);
+    assert!(rendered.contains("broken.tung"), "{}", rendered);
+    let Value::Struct(fields) = from_report(&error, "") else {
+        unreachable!("errors are structs");
+    };
+    assert_eq!(fields.get("line").unwrap().to_string(), "3");
+}
+
+#[test]
+fn modules_are_checked_before_they_run() {
+    let directory: PathBuf = project(
+        "checks",
+        &[
+            ("main.tung", "import flags\nimport flags\nprint(flags.sign(true))\n"),
+            (
+                "flags.tung",
+                "print(\"loading\")\nfn sign(flag) {\n    match flag {\n        true => { return 1 }\n    }\n}\n",
+            ),
+            ("escapes.tung", "print(\"loading\")\nvar path = \"C:\\qux\"\n"),
+        ],
+    );
+    let run: Run = execute_project(&directory, Vec::new());
+    run.result.unwrap();
+    assert_eq!(run.output, "loading\n1\n");
+    // Warned about once, when the module is first loaded
+    assert_eq!(
+        run.error_output
+            .matches("This match on a bool is not exhaustive")
+            .count(),
+        1,
+        "{}",
+        run.error_output
+    );
+    assert!(
+        run.error_output.contains("flags.tung"),
+        "{}",
+        run.error_output
+    );
+
+    fs::write(directory.join("main.tung"), "import escapes\n").unwrap();
+    let run: Run = execute_project(&directory, Vec::new());
+    let error: miette::Report = run.result.unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "SyntaxError");
+    assert_eq!(error.to_string(), "Error: Unknown escape sequence '\\q'.");
+    assert_eq!(run.output, "");
+}
+
+#[test]
+fn structs_from_modules_keep_their_methods() {
+    let directory: PathBuf = project(
+        "structs",
+        &[
+            (
+                "main.tung",
+                r#"
+import geometry
+from geometry import Point
+var p = geometry.Point(3, 4)
+var q = Point(1, 1)
+print(p.norm2(), q.norm2(), Point.origin())
+"#,
+            ),
+            (
+                "geometry.tung",
+                r#"
+struct Point { x, y }
+impl Point {
+    fn origin() {
+        return Point(0, 0)
+    }
+    fn norm2(self) {
+        return self.x * self.x + self.y * self.y
+    }
+}
+"#,
+            ),
+        ],
+    );
+    assert_eq!(
+        run(&directory, Vec::new()).unwrap(),
+        "25 2 Point { x: 0, y: 0 }\n"
+    );
+}
+
+#[test]
+fn instances_keep_their_methods_in_other_modules() {
+    let directory: PathBuf = project(
+        "instances",
+        &[
+            (
+                "main.tung",
+                r#"
+import shapes
+struct Square { side }
+impl Square {
+    fn area(self) {
+        return self.side * self.side
+    }
+}
+print(shapes.describe(Square(3)))
+"#,
+            ),
+            (
+                "shapes.tung",
+                r#"
+fn describe(shape) {
+    return "area " + str(shape.area())
+}
+"#,
+            ),
+        ],
+    );
+    assert_eq!(run(&directory, Vec::new()).unwrap(), "area 9\n");
+}
+
+#[test]
+fn standard_library_modules_come_before_files_of_the_same_name() {
+    let directory: PathBuf = project(
+        "standard",
+        &[
+            (
+                "main.tung",
+                r#"
+import math
+var round = "a variable"
+print(math.round(2.4), round, math.sum([1, 2]))
+import "math.tung"
+print(math.name)
+"#,
+            ),
+            ("math.tung", "var name = \"local math\"\n"),
+        ],
+    );
+    assert_eq!(
+        run(&directory, Vec::new()).unwrap(),
+        "2 a variable 3\nlocal math\n"
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{execute, Run};
+
+/// Runs a program, returning what it wrote to stdout and stderr
+fn run(source: &'static str) -> (String, String) {
+    let run: Run = execute(source, "", |stdlib| stdlib);
+    run.result.unwrap();
+    (run.output, run.error_output)
+}
+
+#[test]
+fn prints_multiple_arguments() {
+    let (out, _) = run(r#"print("a", 1, 2.5, [1, "b"])"#);
+    assert_eq!(out, "a 1 2.5 [1, \"b\"]\n");
+}
+
+#[test]
+fn sep_and_end() {
+    let (out, _) = run(r#"
+        print(1, 2, 3, sep=", ", end="")
+        print("!", end="\n")
+        print()
+    "#);
+    assert_eq!(out, "1, 2, 3!\n\n");
+}
+
+#[test]
+fn eprint_writes_to_error_output() {
+    let (out, err) = run(r#"
+        print("out")
+        eprint("err", 1, sep="-")
+    "#);
+    assert_eq!(out, "out\n");
+    assert_eq!(err, "err-1\n");
+}
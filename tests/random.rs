@@ -0,0 +1,72 @@
+mod common;
+
+use common::{execute, parse, Run};
+use tung_lang::eval::std::std_random::Rng;
+use tung_lang::testing::{run_tests, TestResult};
+
+const DRAWS: &str = r#"
+import random
+print(random.random(), random.randint(0 - 5, 5), random.choice("xyz"), random.shuffle([1, 2, 3]))
+"#;
+
+/// Runs a program with the random numbers starting from `seed`, returning what it printed
+fn run(source: &'static str, seed: u64) -> String {
+    let run: Run = execute(source, "", |stdlib| stdlib.with_seed(seed));
+    run.result.unwrap();
+    run.output
+}
+
+#[test]
+fn a_seed_repeats_the_same_numbers() {
+    assert_eq!(run(DRAWS, 1), run(DRAWS, 1));
+    assert_ne!(run(DRAWS, 1), run(DRAWS, 2));
+}
+
+#[test]
+fn draws_stay_in_range() {
+    let mut rng: Rng = Rng::new(0);
+    let mut seen: [bool; 6] = [false; 6];
+    for _ in 0..1000 {
+        let x: f64 = rng.next_f64();
+        assert!((0.0..1.0).contains(&x));
+        seen[rng.below(6) as usize] = true;
+    }
+    assert!(
+        seen.iter().all(|&s| s),
+        "every value below 6 should come up"
+    );
+
+    let output: String = run(
+        r#"
+import random
+var ok = true
+for i in range(500) {
+    var n = random.randint(3, 5)
+    if n !in [3, 4, 5] {
+        ok = false
+    }
+}
+var picked = random.sample(range(10), 10)
+print(ok, sort(picked) == range(10), random.randint(7, 7))
+"#,
+        3,
+    );
+    assert_eq!(output, "true true 7\n");
+}
+
+#[test]
+fn tests_run_with_the_given_seed() {
+    let parsed = parse(
+        r#"
+import random
+fn test_first_draw() {
+    print(random.randint(1, 1000000))
+}
+"#,
+    );
+    let outputs: Vec<String> = (0..2)
+        .map(|_| run_tests(parsed.clone(), None, Some(9)))
+        .map(|results: Vec<TestResult>| results[0].output.clone())
+        .collect();
+    assert_eq!(outputs[0], outputs[1]);
+}
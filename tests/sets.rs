@@ -0,0 +1,67 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn literals_deduplicate_in_insertion_order() {
+    let out = run(r#"
+        print({3, 1, 3, 2, 1})
+        print(set([1, 1, 2]), set(), {})
+        print(len({"a", "a", "b"}))
+    "#)
+    .unwrap();
+    assert_eq!(out, "{3, 1, 2}\n{1, 2} set() {}\n2\n");
+}
+
+#[test]
+fn set_algebra() {
+    let out = run(r#"
+        var a = {1, 2, 3}
+        var b = {3, 4}
+        print(a | b, a & b, a - b, a ^ b)
+    "#)
+    .unwrap();
+    assert_eq!(out, "{1, 2, 3, 4} {3} {1, 2} {1, 2, 4}\n");
+}
+
+#[test]
+fn subset_comparisons() {
+    let out = run(r#"
+        var a = {1, 2, 3}
+        print({1, 2} <= a, {1, 2} < a, a < a, a <= a, a > {1}, {1} >= a)
+        print(a == {3, 2, 1}, a != {1})
+    "#)
+    .unwrap();
+    assert_eq!(out, "true true false true true false\ntrue true\n");
+}
+
+#[test]
+fn add_remove_and_membership() {
+    let out = run(r#"
+        var s = set()
+        s.add(1)
+        s.add(1)
+        s.add((2, 3))
+        print(s, (2, 3) in s, 4 !in s)
+        s.remove(1)
+        print(s, add(s, 5), remove(s, (2, 3)))
+    "#)
+    .unwrap();
+    assert_eq!(out, "{1, (2, 3)} true true\n{(2, 3)} {(2, 3), 5} set()\n");
+}
+
+#[test]
+fn float_items_hash_consistently() {
+    let out = run(r#"
+        var s = {0.0, 1.5}
+        print(0 - 0.0 in s, 1.5 in s, len({0.0, 0 - 0.0}))
+    "#)
+    .unwrap();
+    assert_eq!(out, "true true 1\n");
+}
+
+#[test]
+fn unhashable_item_is_an_error() {
+    let error = run("var s = {[1]}").unwrap_err();
+    assert!(error.to_string().contains("Unhashable type 'array'"));
+}
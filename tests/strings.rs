@@ -0,0 +1,54 @@
+mod common;
+
+use common::{parse, run};
+use tung_lang::eval::string_literal::check_escapes;
+
+/// The message and labelled source text of the escape error a program is rejected with
+fn escape_error(source: &'static str) -> (String, &'static str) {
+    let error: miette::Report = check_escapes(parse(source)).unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "SyntaxError");
+    let label = error.labels().unwrap().next().unwrap();
+    let text: &'static str = &source[label.offset()..label.offset() + label.len()];
+    (error.to_string(), text)
+}
+
+#[test]
+fn escapes_in_plain_strings() {
+    let output: String = run(r#"
+print("a\tb\\c\"d", 'it\'s', "\u{41}\u{1F600}\u{e9}", len("\n\0"))
+print("""two
+"lines"\u{21}""", r"C:\new\u{41}")
+"#)
+    .unwrap();
+    assert_eq!(
+        output,
+        "a\tb\\c\"d it's A\u{1F600}\u{e9} 2\ntwo\n\"lines\"! C:\\new\\u{41}\n"
+    );
+}
+
+#[test]
+fn escapes_in_fstrings() {
+    let output: String = run(r#"
+var n = 2
+print(f"x\u{41}y", f"\u{41}{n}\u{42}", f"{n}\t{{\u{7B}}}\"", f"\\{n}")
+print(f"a\u{7B}\u{7B}b", f"\u{7D}}}{{")
+"#)
+    .unwrap();
+    assert_eq!(output, "xAy A2B 2\t{{}\" \\2\na{{b }}{\n");
+}
+
+#[test]
+fn invalid_escapes_are_reported_before_the_program_runs() {
+    assert_eq!(
+        escape_error("print(\"ok\")\nprint(\"a\\qb\")"),
+        ("Error: Unknown escape sequence '\\q'.".to_string(), "\\q")
+    );
+    assert_eq!(
+        escape_error("var n = 1\nprint(f\"{n} \\q\")"),
+        ("Error: Unknown escape sequence '\\q'.".to_string(), "\\q")
+    );
+    assert_eq!(escape_error("print(\"\\u{110000}\")").1, "\\u{110000}");
+    assert_eq!(escape_error("print(f\"\\u{}\")").1, "\\u{}");
+    assert_eq!(escape_error("print('\\u41')").1, "\\u");
+    assert!(check_escapes(parse("print(r\"\\q\", f\"\\u{41}\")")).is_ok());
+}
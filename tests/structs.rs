@@ -0,0 +1,97 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn methods_added_later_reach_existing_instances() {
+    let output: String = run(r#"
+struct P { x, y }
+fn f(p) {
+    return p.norm()
+}
+var early = P(3, 4)
+impl P {
+    fn norm(self) {
+        return self.x * self.x + self.y * self.y
+    }
+}
+print(f(P(1, 2)), early.norm())
+impl P {
+    fn sum(self) {
+        return self.x + self.y
+    }
+}
+print(f(early), early.sum())
+"#)
+    .unwrap();
+    assert_eq!(output, "5 25\n25 7\n");
+}
+
+#[test]
+fn methods_do_not_depend_on_names_in_scope() {
+    let output: String = run(r#"
+struct P { x, y }
+impl P {
+    fn norm(self) {
+        return self.x * self.x + self.y * self.y
+    }
+}
+fn shadowed(p) {
+    var P = "not a struct"
+    return p.norm()
+}
+var p = P(1, 2)
+var Q = P
+var P = 5
+print(shadowed(p), p.norm(), Q(2, 2).norm())
+"#)
+    .unwrap();
+    assert_eq!(output, "5 5 8\n");
+}
+
+#[test]
+fn methods_can_update_self_and_call_each_other() {
+    let output: String = run(r#"
+struct Counter { count }
+impl Counter {
+    fn new() {
+        return Counter(0)
+    }
+    fn bump(self, by = 1) {
+        self.count += by
+    }
+    fn twice(self) {
+        self.bump()
+        self.bump()
+        return self.count
+    }
+}
+var c = Counter.new()
+c.bump(by = 5)
+print(c.twice(), c)
+"#)
+    .unwrap();
+    assert_eq!(output, "7 Counter { count: 7 }\n");
+}
+
+#[test]
+fn missing_methods_and_types_are_errors() {
+    let error = run(r#"
+struct P { x }
+P(1).norm()
+"#)
+    .unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "AttributeError");
+    assert_eq!(error.to_string(), "Error: Struct 'P' has no method 'norm'.");
+
+    let error = run(r#"
+struct P { x }
+var p = P(1)
+p = P(1, 2)
+"#)
+    .unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "TypeError");
+
+    let error = run("impl Missing {\n}\n").unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "NameError");
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::parse;
+use tung_lang::testing::{run_tests, TestResult};
+
+/// Runs the test functions of a program
+fn run(source: &'static str) -> Vec<TestResult> {
+    run_tests(parse(source), None, None)
+}
+
+#[test]
+fn assert_raises_an_assertion_error() {
+    let results: Vec<TestResult> = run(r#"
+fn test_true() {
+    assert(1 + 1 == 2)
+    assert([1], "non-empty arrays are truthy")
+}
+fn test_false() {
+    assert(0)
+}
+fn test_message() {
+    assert(1 > 2, f"expected {1} > {2}")
+}
+    "#);
+    assert!(results[0].passed());
+    let error = results[1].error.as_ref().unwrap();
+    assert_eq!(error.to_string(), "Error: Assertion failed.");
+    assert_eq!(error.code().unwrap().to_string(), "AssertionError");
+    assert_eq!(
+        results[2].error.as_ref().unwrap().to_string(),
+        "Error: Assertion failed: expected 1 > 2"
+    );
+}
+
+#[test]
+fn runs_each_test_function_in_isolation() {
+    let results: Vec<TestResult> = run(r#"
+var count = 0
+print("setup")
+fn helper() {
+    return 42
+}
+fn test_first() {
+    count += 1
+    assert(count == 1)
+}
+fn test_second() {
+    count += 1
+    assert(count == 1, "tests share state")
+    print("second ran")
+}
+fn not_a_test() {
+    assert(false)
+}
+fn test_error() {
+    var x = helper() / 0
+}
+    "#);
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["test_first", "test_second", "test_error"]);
+    assert!(results[0].passed());
+    assert!(results[1].passed());
+    assert_eq!(results[1].output, "setup\nsecond ran\n");
+    assert_eq!(
+        results[2]
+            .error
+            .as_ref()
+            .unwrap()
+            .code()
+            .unwrap()
+            .to_string(),
+        "ZeroDivisionError"
+    );
+}
+
+#[test]
+fn assertion_errors_can_be_caught() {
+    let results: Vec<TestResult> = run(r#"
+fn test_raises() {
+    try {
+        assert(false, "boom")
+    } catch AssertionError e {
+        assert(e.message == "Assertion failed: boom")
+        return true
+    }
+    assert(false, "assert did not raise")
+}
+    "#);
+    assert!(results[0].passed());
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{int, variables};
+use tung_lang::value::{StringValue, Value};
+
+#[test]
+fn tuple_literals() {
+    let variables = variables(
+        r#"
+        var empty = ()
+        var single = (1,)
+        var grouped = (1)
+        var pair = (1, "a")
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["empty"], Value::Tuple(vec![]));
+    assert_eq!(variables["single"], Value::Tuple(vec![int(1)]));
+    assert_eq!(variables["grouped"], int(1));
+    assert_eq!(
+        variables["pair"],
+        Value::Tuple(vec![int(1), Value::String(StringValue("a".to_string()))])
+    );
+}
+
+#[test]
+fn destructuring_declarations() {
+    let variables = variables(
+        r#"
+        fn sum_and_difference(a, b) {
+            return a + b, a - b
+        }
+        var (q, r) = sum_and_difference(7, 2)
+        var x, y = 1, 2
+        var (a, (b, c)) = (1, [2, 3])
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["q"], int(9));
+    assert_eq!(variables["r"], int(5));
+    assert_eq!((&variables["x"], &variables["y"]), (&int(1), &int(2)));
+    assert_eq!(
+        (&variables["a"], &variables["b"], &variables["c"]),
+        (&int(1), &int(2), &int(3))
+    );
+}
+
+#[test]
+fn swap() {
+    let variables = variables(
+        r#"
+        var a = 1
+        var b = 2
+        a, b = b, a
+    "#,
+    )
+    .unwrap();
+    assert_eq!(variables["a"], int(2));
+    assert_eq!(variables["b"], int(1));
+}
+
+#[test]
+fn for_loop_destructures_items() {
+    let variables = variables(
+        r#"
+        var d = {"a": 1, "b": 2, "c": 3}
+        var keys_seen = ""
+        var total = 0
+        for (k, v) in items(d) {
+            keys_seen += k
+            total += v
+        }
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        variables["keys_seen"],
+        Value::String(StringValue("abc".to_string()))
+    );
+    assert_eq!(variables["total"], int(6));
+    assert!(!variables.contains_key("k"));
+}
+
+#[test]
+fn unpacking_wrong_count_is_an_error() {
+    let error = variables("var (a, b) = (1, 2, 3)").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Error: Expected 2 values to unpack, got 3."
+    );
+    let error = variables("var (a, b) = 5").unwrap_err();
+    assert_eq!(error.to_string(), "Error: Cannot unpack int into 2 names.");
+}